@@ -1,13 +1,22 @@
 extern crate lv2;
 extern crate stutter;
+#[cfg(test)]
+mod presets_ttl;
 use lv2::prelude::*;
-use stutter::Stutter;
+use std::collections::VecDeque;
+use stutter::{
+  note_values::{get_midi_note_value, is_note_channel, NOTE_VALUES},
+  sync::{get_pulse_length, get_synced_pulse_time},
+  tempo_factor::{clamp_ratio, map_tempo_factor},
+  ClockFollower, PanPattern, Settings, Stutter, TapTempo,
+};
 
 #[derive(URIDCollection)]
 struct URIDs {
   atom: AtomURIDCollection,
   unit: UnitURIDCollection,
   time: TimeURIDCollection,
+  midi: MidiURIDCollection,
 }
 
 #[derive(FeatureCollection)]
@@ -15,66 +24,412 @@ pub struct InitFeatures<'a> {
   map: LV2Map<'a>,
 }
 
-#[derive(PortCollection)]
-struct Ports {
-  control: InputPort<AtomPort>,
-  on: InputPort<InPlaceControl>,
-  trigger: InputPort<InPlaceControl>,
-  auto: InputPort<InPlaceControl>,
-  sync: InputPort<InPlaceControl>,
-  mix: InputPort<InPlaceControl>,
-  pulse: InputPort<InPlaceControl>,
-  tempo_factor: InputPort<InPlaceControl>,
-  duration: InputPort<InPlaceControl>,
-  chance: InputPort<InPlaceControl>,
-  half_notes: InputPort<InPlaceControl>,
-  seven_sixteenth_notes: InputPort<InPlaceControl>,
-  six_sixteenth_notes: InputPort<InPlaceControl>,
-  half_triplet_notes: InputPort<InPlaceControl>,
-  five_sixteenth_notes: InputPort<InPlaceControl>,
-  quarter_notes: InputPort<InPlaceControl>,
-  three_sixteenth_notes: InputPort<InPlaceControl>,
-  quarter_triplet_notes: InputPort<InPlaceControl>,
-  eighth_notes: InputPort<InPlaceControl>,
-  eighth_triplet_notes: InputPort<InPlaceControl>,
-  sixteenth_notes: InputPort<InPlaceControl>,
-  sixteenth_triplet_notes: InputPort<InPlaceControl>,
-  thirty_second_notes: InputPort<InPlaceControl>,
-  thirty_second_triplet_notes: InputPort<InPlaceControl>,
-  sixty_fourth_notes: InputPort<InPlaceControl>,
+const CV_GATE_THRESHOLD: f32 = 1.;
+const MAX_NOTE_TRIGGERS: usize = 64;
+
+struct Controls {
+  on: bool,
+  trigger: bool,
+  auto: bool,
+  sync: bool,
+  mix: i32,
+  pulse: f32,
+  tempo_factor: f32,
+  duration: f32,
+  chance: f32,
+  pan_pattern: PanPattern,
+  pan_amount: f32,
+  width: f32,
+  sidechain: bool,
+  sensitivity: f32,
+  hold_off: f32,
+  quantize: f32,
+  quantize_sensitivity: f32,
+  clock: bool,
+  ppqn: f32,
+  tap: bool,
+  sync_mode: i32,
+  stretch: bool,
+  freeze: bool,
+  forced_trigger: bool,
+  forced_note: usize,
+  forced_repeats: f32,
+  note_triggers: bool,
+  note_channel: i32,
+}
+
+// CV inputs are read per sample, so they can be patched from other plugins with sample accuracy.
+struct CvInputs {
+  trigger: f32,
+  on: f32,
+  duration: f32,
+  chance: f32,
+  clock: f32,
+}
+
+trait StutterPorts {
+  fn control(&self) -> &InputPort<AtomPort>;
+  fn controls(&self) -> Controls;
+  fn cv_inputs(&self, index: usize) -> CvInputs;
+  fn set_probability<const N: usize>(&self, stutter: &mut Stutter<N>);
+}
+
+// Every plugin variant shares the same control ports and only differs in its audio ports.
+macro_rules! stutter_ports {
+  (
+    $name:ident { $($audio_port:ident: $audio_port_type:ty,)* }
+    { $($sidechain_port:ident: $sidechain_port_type:ty,)* }
+  ) => {
+    #[derive(PortCollection)]
+    struct $name {
+      control: InputPort<AtomPort>,
+      on: InputPort<InPlaceControl>,
+      trigger: InputPort<InPlaceControl>,
+      auto: InputPort<InPlaceControl>,
+      sync: InputPort<InPlaceControl>,
+      mix: InputPort<InPlaceControl>,
+      pulse: InputPort<InPlaceControl>,
+      tempo_factor: InputPort<InPlaceControl>,
+      duration: InputPort<InPlaceControl>,
+      chance: InputPort<InPlaceControl>,
+      half_notes: InputPort<InPlaceControl>,
+      seven_sixteenth_notes: InputPort<InPlaceControl>,
+      six_sixteenth_notes: InputPort<InPlaceControl>,
+      half_triplet_notes: InputPort<InPlaceControl>,
+      five_sixteenth_notes: InputPort<InPlaceControl>,
+      quarter_notes: InputPort<InPlaceControl>,
+      three_sixteenth_notes: InputPort<InPlaceControl>,
+      quarter_triplet_notes: InputPort<InPlaceControl>,
+      eighth_notes: InputPort<InPlaceControl>,
+      eighth_triplet_notes: InputPort<InPlaceControl>,
+      sixteenth_notes: InputPort<InPlaceControl>,
+      sixteenth_triplet_notes: InputPort<InPlaceControl>,
+      thirty_second_notes: InputPort<InPlaceControl>,
+      thirty_second_triplet_notes: InputPort<InPlaceControl>,
+      sixty_fourth_notes: InputPort<InPlaceControl>,
+      $($audio_port: $audio_port_type,)*
+      trigger_cv_output: OutputPort<InPlaceCV>,
+      repeat_trigger_cv_output: OutputPort<InPlaceCV>,
+      pan_pattern: InputPort<InPlaceControl>,
+      pan_amount: InputPort<InPlaceControl>,
+      width: InputPort<InPlaceControl>,
+      sidechain: InputPort<InPlaceControl>,
+      sensitivity: InputPort<InPlaceControl>,
+      hold_off: InputPort<InPlaceControl>,
+      quantize: InputPort<InPlaceControl>,
+      $($sidechain_port: $sidechain_port_type,)*
+      trigger_cv_input: InputPort<InPlaceCV>,
+      on_cv_input: InputPort<InPlaceCV>,
+      duration_cv_input: InputPort<InPlaceCV>,
+      chance_cv_input: InputPort<InPlaceCV>,
+      clock: InputPort<InPlaceControl>,
+      ppqn: InputPort<InPlaceControl>,
+      clock_cv_input: InputPort<InPlaceCV>,
+      tap: InputPort<InPlaceControl>,
+      free_ratio: InputPort<InPlaceControl>,
+      ratio: InputPort<InPlaceControl>,
+      sync_mode: InputPort<InPlaceControl>,
+      stretch: InputPort<InPlaceControl>,
+      triplet_balance: InputPort<InPlaceControl>,
+      odd_balance: InputPort<InPlaceControl>,
+      tilt: InputPort<InPlaceControl>,
+      freeze: InputPort<InPlaceControl>,
+      forced_trigger: InputPort<InPlaceControl>,
+      forced_note: InputPort<InPlaceControl>,
+      forced_repeats: InputPort<InPlaceControl>,
+      quantize_sensitivity: InputPort<InPlaceControl>,
+      note_triggers: InputPort<InPlaceControl>,
+      note_channel: InputPort<InPlaceControl>,
+    }
+
+    impl StutterPorts for $name {
+      fn control(&self) -> &InputPort<AtomPort> {
+        &self.control
+      }
+
+      fn controls(&self) -> Controls {
+        Controls {
+          on: self.on.get() == 1.,
+          trigger: self.trigger.get() == 1.,
+          auto: self.auto.get() == 1.,
+          sync: self.sync.get() == 1.,
+          mix: self.mix.get() as i32 - 1,
+          pulse: self.pulse.get(),
+          tempo_factor: if self.free_ratio.get() == 1. {
+            clamp_ratio(self.ratio.get())
+          } else {
+            map_tempo_factor(self.tempo_factor.get())
+          },
+          duration: self.duration.get(),
+          chance: self.chance.get(),
+          pan_pattern: PanPattern::from(self.pan_pattern.get() as i32),
+          pan_amount: self.pan_amount.get(),
+          width: self.width.get(),
+          sidechain: self.sidechain.get() == 1.,
+          sensitivity: self.sensitivity.get(),
+          hold_off: self.hold_off.get(),
+          quantize: self.quantize.get(),
+          quantize_sensitivity: self.quantize_sensitivity.get(),
+          clock: self.clock.get() == 1.,
+          ppqn: self.ppqn.get(),
+          tap: self.tap.get() == 1.,
+          sync_mode: self.sync_mode.get() as i32,
+          stretch: self.stretch.get() == 1.,
+          freeze: self.freeze.get() == 1.,
+          forced_trigger: self.forced_trigger.get() == 1.,
+          forced_note: (self.forced_note.get().max(0.) as usize).min(NOTE_VALUES.len() - 1),
+          forced_repeats: self.forced_repeats.get(),
+          note_triggers: self.note_triggers.get() == 1.,
+          note_channel: self.note_channel.get() as i32,
+        }
+      }
+
+      fn cv_inputs(&self, index: usize) -> CvInputs {
+        CvInputs {
+          trigger: self.trigger_cv_input[index].get(),
+          on: self.on_cv_input[index].get(),
+          duration: self.duration_cv_input[index].get(),
+          chance: self.chance_cv_input[index].get(),
+          clock: self.clock_cv_input[index].get(),
+        }
+      }
+
+      fn set_probability<const N: usize>(&self, stutter: &mut Stutter<N>) {
+        stutter.set_probability_macros(
+          self.triplet_balance.get(),
+          self.odd_balance.get(),
+          self.tilt.get(),
+        );
+        stutter.set_probability(
+          self.half_notes.get(),
+          self.seven_sixteenth_notes.get(),
+          self.six_sixteenth_notes.get(),
+          self.half_triplet_notes.get(),
+          self.five_sixteenth_notes.get(),
+          self.quarter_notes.get(),
+          self.three_sixteenth_notes.get(),
+          self.quarter_triplet_notes.get(),
+          self.eighth_notes.get(),
+          self.eighth_triplet_notes.get(),
+          self.sixteenth_notes.get(),
+          self.sixteenth_triplet_notes.get(),
+          self.thirty_second_notes.get(),
+          self.thirty_second_triplet_notes.get(),
+          self.sixty_fourth_notes.get(),
+        );
+      }
+    }
+  };
+}
+
+stutter_ports!(Ports {
   input_left: InputPort<InPlaceAudio>,
   input_right: InputPort<InPlaceAudio>,
   output_left: OutputPort<InPlaceAudio>,
   output_right: OutputPort<InPlaceAudio>,
-  trigger_cv_output: OutputPort<InPlaceCV>,
-  repeat_trigger_cv_output: OutputPort<InPlaceCV>,
-}
+} {
+  sidechain_left: InputPort<InPlaceAudio>,
+  sidechain_right: InputPort<InPlaceAudio>,
+});
 
-#[uri("https://github.com/davemollen/dm-Stutter")]
-struct DmStutter {
-  stutter: Stutter,
+stutter_ports!(MonoPorts {
+  input: InputPort<InPlaceAudio>,
+  output: OutputPort<InPlaceAudio>,
+} {
+  sidechain_input: InputPort<InPlaceAudio>,
+});
+
+stutter_ports!(MonoToStereoPorts {
+  input: InputPort<InPlaceAudio>,
+  output_left: OutputPort<InPlaceAudio>,
+  output_right: OutputPort<InPlaceAudio>,
+} {
+  sidechain_input: InputPort<InPlaceAudio>,
+});
+
+struct StutterProcessor<const N: usize> {
+  stutter: Stutter<N>,
+  clock_follower: ClockFollower,
+  tap_tempo: TapTempo,
   urids: URIDs,
   bpm: f32,
+  beats_per_bar: f32,
+  beat_unit: f32,
+  prev_pulse: f32,
+  prev_forced_trigger: bool,
+  // Note ons are queued with their frame offset, so they trigger on the sample they arrive at
+  note_triggers: VecDeque<(u32, f32)>,
+  frame: u32,
 }
 
-impl DmStutter {
-  fn get_synced_pulse_time(&self, ports: &mut Ports) -> f32 {
-    60000. / self.bpm * Self::map_tempo_factor(ports.tempo_factor.get())
+impl<const N: usize> StutterProcessor<N> {
+  fn new(plugin_info: &PluginInfo, features: &mut InitFeatures<'static>) -> Option<Self> {
+    Some(Self {
+      bpm: 120.,
+      beats_per_bar: 4.,
+      beat_unit: 4.,
+      stutter: Stutter::new(plugin_info.sample_rate() as f32),
+      clock_follower: ClockFollower::new(plugin_info.sample_rate() as f32),
+      tap_tempo: TapTempo::new(plugin_info.sample_rate() as f32),
+      prev_pulse: 0.,
+      prev_forced_trigger: false,
+      note_triggers: VecDeque::with_capacity(MAX_NOTE_TRIGGERS),
+      frame: 0,
+      urids: features.map.populate_collection()?,
+    })
   }
 
-  fn set_bpm(&mut self, ports: &mut Ports) {
-    let control_sequence = match ports
-      .control
-      .read(self.urids.atom.sequence, self.urids.unit.beat)
+  fn get_controls(&mut self, ports: &impl StutterPorts) -> Controls {
+    let mut controls = ports.controls();
+
+    self.frame = 0;
+    self.note_triggers.clear();
+    self.read_control_port(ports.control(), &controls);
+    self.clock_follower.set_ppqn(controls.ppqn);
+
+    if controls.clock {
+      if let Some(pulse) = self.clock_follower.get_pulse_time() {
+        controls.pulse = pulse
+          * get_pulse_length(self.beats_per_bar, self.beat_unit, controls.sync_mode)
+          * controls.tempo_factor;
+      }
+    } else if controls.sync {
+      controls.pulse = self.get_synced_pulse_time(controls.tempo_factor, controls.sync_mode);
+    } else {
+      controls.pulse = self.get_tapped_pulse_time(controls.pulse, controls.tap);
+    }
+
+    self
+      .stutter
+      .set_auto_pan(controls.pan_pattern, controls.pan_amount, controls.width);
+    self
+      .stutter
+      .set_sidechain(controls.sidechain, controls.sensitivity, controls.hold_off);
+    self
+      .stutter
+      .set_transient_quantize(controls.quantize, controls.quantize_sensitivity);
+    self.stutter.set_time_stretch(controls.stretch);
+    self.stutter.set_freeze(controls.freeze);
+
+    // The forced trigger responds to its rising edge, just like the momentary trigger control
+    if controls.forced_trigger && !self.prev_forced_trigger {
+      self.stutter.trigger_with(
+        NOTE_VALUES[controls.forced_note].1,
+        (controls.forced_repeats >= 1.).then_some(controls.forced_repeats),
+      );
+    }
+    self.prev_forced_trigger = controls.forced_trigger;
+
+    ports.set_probability(&mut self.stutter);
+
+    controls
+  }
+
+  fn process(
+    &mut self,
+    input: [f32; N],
+    sidechain: f32,
+    controls: &Controls,
+    cv_inputs: CvInputs,
+  ) -> ([f32; N], f32, f32) {
+    // The trigger & on CV inputs act like gates next to their control ports. The stutter only
+    // responds to the rising edge of the trigger, just like with the momentary trigger control.
+    self
+      .clock_follower
+      .process_gate(cv_inputs.clock >= CV_GATE_THRESHOLD, 0);
+    self.clock_follower.advance(1);
+    self.tap_tempo.advance(1);
+    while let Some((_, time_fraction)) = self
+      .note_triggers
+      .pop_front_if(|(frame, _)| *frame <= self.frame)
     {
+      self.stutter.trigger_with(time_fraction, None);
+    }
+    self.frame += 1;
+
+    let settings = Settings {
+      on: controls.on || cv_inputs.on >= CV_GATE_THRESHOLD,
+      trigger: controls.trigger || cv_inputs.trigger >= CV_GATE_THRESHOLD,
+      auto_trigger: controls.auto,
+      mix: controls.mix,
+      pulse: controls.pulse,
+      duration: (controls.duration + cv_inputs.duration * 0.1).clamp(0., 1.),
+      chance: (controls.chance + cv_inputs.chance * 0.1).clamp(0., 1.),
+      is_momentary_trigger: true,
+    };
+
+    let (stutter_output, trigger_cv_output, repeat_trigger_cv_output) =
+      self.stutter.process(input, sidechain, &settings);
+
+    (
+      stutter_output,
+      if trigger_cv_output { 10. } else { 0. },
+      if repeat_trigger_cv_output { 10. } else { 0. },
+    )
+  }
+
+  // The tapped tempo overrides the pulse knob until the knob is moved again.
+  fn get_tapped_pulse_time(&mut self, pulse: f32, tap: bool) -> f32 {
+    let is_tapped = self.tap_tempo.process(tap, true);
+    if pulse != self.prev_pulse && !is_tapped {
+      self.tap_tempo.reset();
+    }
+    self.prev_pulse = pulse;
+
+    self
+      .tap_tempo
+      .get_pulse_time()
+      .unwrap_or(pulse)
+      .clamp(10., 3000.)
+  }
+
+  // LV2 expresses the tempo in beat units per minute, so it's converted to quarter notes first.
+  fn get_synced_pulse_time(&self, tempo_factor: f32, sync_mode: i32) -> f32 {
+    get_synced_pulse_time(
+      self.bpm * 4. / self.beat_unit.max(1.),
+      self.beats_per_bar,
+      self.beat_unit,
+      sync_mode,
+    ) * tempo_factor
+  }
+
+  // Reads the host tempo, MIDI clock messages and MIDI notes. Clock ticks and notes are passed
+  // with their frame offset, because they're read before the samples of this block are processed.
+  fn read_control_port(&mut self, control: &InputPort<AtomPort>, controls: &Controls) {
+    let control_sequence = match control.read(self.urids.atom.sequence, self.urids.unit.beat) {
       Some(sequence_iter) => sequence_iter,
       None => return,
     };
 
-    for (_, atom) in control_sequence {
+    for (timestamp, atom) in control_sequence {
+      let frame = timestamp.as_frames().unwrap_or(0).max(0) as u32;
+      if let Some(message) = atom.read(self.urids.midi.raw, ()) {
+        match message.first() {
+          Some(0xF8) => self.clock_follower.tick(frame),
+          Some(0xFA) => self.clock_follower.restart(),
+          // Note ons from note 36 up trigger right away with their own note value, when note
+          // triggers are enabled and the channel matches
+          Some(status)
+            if controls.note_triggers
+              && status & 0xF0 == 0x90
+              && is_note_channel(status & 0x0F, controls.note_channel)
+              && message.get(2).is_some_and(|v| *v > 0) =>
+          {
+            if let Some(time_fraction) = message.get(1).and_then(|note| get_midi_note_value(*note))
+            {
+              if self.note_triggers.len() < MAX_NOTE_TRIGGERS {
+                self.note_triggers.push_back((frame, time_fraction));
+              }
+            }
+          }
+          _ => (),
+        }
+        continue;
+      }
+
       let (object_header, object_reader) = match atom.read(self.urids.atom.object, ()) {
         Some(x) => x,
-        None => return,
+        None => continue,
       };
 
       if object_header.otype != self.urids.time.position_class {
@@ -82,27 +437,27 @@ impl DmStutter {
       }
 
       for (property_header, property) in object_reader {
-        if property_header.key != self.urids.time.beats_per_minute {
-          continue;
-        }
-
-        if let Some(bpm) = property.read(self.urids.atom.float, ()) {
-          self.bpm = bpm;
+        if property_header.key == self.urids.time.beats_per_minute {
+          if let Some(bpm) = property.read(self.urids.atom.float, ()) {
+            self.bpm = bpm;
+          }
+        } else if property_header.key == self.urids.time.beats_per_bar {
+          if let Some(beats_per_bar) = property.read(self.urids.atom.float, ()) {
+            self.beats_per_bar = beats_per_bar;
+          }
+        } else if property_header.key == self.urids.time.beat_unit {
+          if let Some(beat_unit) = property.read(self.urids.atom.int, ()) {
+            self.beat_unit = beat_unit as f32;
+          }
         }
       }
     }
   }
+}
 
-  fn map_tempo_factor(tempo_factor: f32) -> f32 {
-    match tempo_factor {
-      0. => 0.25,
-      1. => 0.5,
-      2. => 1.,
-      3. => 2.,
-      4. => 4.,
-      _ => panic!("Unsupported value for tempo factor was found."),
-    }
-  }
+#[uri("https://github.com/davemollen/dm-Stutter")]
+struct DmStutter {
+  processor: StutterProcessor<2>,
 }
 
 impl Plugin for DmStutter {
@@ -116,78 +471,166 @@ impl Plugin for DmStutter {
   // Create a new instance of the plugin; Trivial in this case.
   fn new(plugin_info: &PluginInfo, features: &mut Self::InitFeatures) -> Option<Self> {
     Some(Self {
-      bpm: 120.,
-      stutter: Stutter::new(plugin_info.sample_rate() as f32),
-      urids: features.map.populate_collection()?,
+      processor: StutterProcessor::new(plugin_info, features)?,
     })
   }
 
   // Process a chunk of audio. The audio ports are dereferenced to slices, which the plugin
   // iterates over.
   fn run(&mut self, ports: &mut Ports, _features: &mut Self::AudioFeatures, _sample_count: u32) {
-    let on = ports.on.get() == 1.;
-    let trigger = ports.trigger.get() == 1.;
-    let auto = ports.auto.get() == 1.;
-    let mix = ports.mix.get() as i32 - 1;
-
-    let pulse = if ports.sync.get() == 1. {
-      self.set_bpm(ports);
-      self.get_synced_pulse_time(ports)
-    } else {
-      ports.pulse.get()
-    };
-    let duration = ports.duration.get();
-    let chance = ports.chance.get();
-
-    self.stutter.set_probability(
-      ports.half_notes.get(),
-      ports.seven_sixteenth_notes.get(),
-      ports.six_sixteenth_notes.get(),
-      ports.half_triplet_notes.get(),
-      ports.five_sixteenth_notes.get(),
-      ports.quarter_notes.get(),
-      ports.three_sixteenth_notes.get(),
-      ports.quarter_triplet_notes.get(),
-      ports.eighth_notes.get(),
-      ports.eighth_triplet_notes.get(),
-      ports.sixteenth_notes.get(),
-      ports.sixteenth_triplet_notes.get(),
-      ports.thirty_second_notes.get(),
-      ports.thirty_second_triplet_notes.get(),
-      ports.sixty_fourth_notes.get(),
-    );
+    let controls = self.processor.get_controls(ports);
 
     let input_channels = ports.input_left.iter().zip(ports.input_right.iter());
     let output_channels = ports.output_left.iter().zip(ports.output_right.iter());
+    let sidechain_channels = ports
+      .sidechain_left
+      .iter()
+      .zip(ports.sidechain_right.iter());
     let trigger_cv_output = ports.trigger_cv_output.iter();
     let repeat_trigger_cv_output = ports.repeat_trigger_cv_output.iter();
 
     for (
-      (((input_left, input_right), (output_left, output_right)), trigger_cv_output),
-      repeat_trigger_cv_output,
+      index,
+      (
+        (
+          (
+            ((input_left, input_right), (output_left, output_right)),
+            (sidechain_left, sidechain_right),
+          ),
+          trigger_cv_output,
+        ),
+        repeat_trigger_cv_output,
+      ),
     ) in input_channels
       .zip(output_channels)
+      .zip(sidechain_channels)
       .zip(trigger_cv_output)
       .zip(repeat_trigger_cv_output)
+      .enumerate()
     {
-      let stutter_output = self.stutter.process(
-        (input_left.get(), input_right.get()),
-        on,
-        trigger,
-        auto,
-        mix,
-        pulse,
-        duration,
-        chance,
-        true,
+      let sidechain = (sidechain_left.get() + sidechain_right.get()) * 0.5;
+      let ([stutter_output_left, stutter_output_right], trigger_cv, repeat_trigger_cv) =
+        self.processor.process(
+          [input_left.get(), input_right.get()],
+          sidechain,
+          &controls,
+          ports.cv_inputs(index),
+        );
+      output_left.set(stutter_output_left);
+      output_right.set(stutter_output_right);
+      trigger_cv_output.set(trigger_cv);
+      repeat_trigger_cv_output.set(repeat_trigger_cv);
+    }
+  }
+}
+
+#[uri("https://github.com/davemollen/dm-Stutter#mono")]
+struct DmStutterMono {
+  processor: StutterProcessor<1>,
+}
+
+impl Plugin for DmStutterMono {
+  type Ports = MonoPorts;
+  type InitFeatures = InitFeatures<'static>;
+  type AudioFeatures = ();
+
+  fn new(plugin_info: &PluginInfo, features: &mut Self::InitFeatures) -> Option<Self> {
+    Some(Self {
+      processor: StutterProcessor::new(plugin_info, features)?,
+    })
+  }
+
+  fn run(
+    &mut self,
+    ports: &mut MonoPorts,
+    _features: &mut Self::AudioFeatures,
+    _sample_count: u32,
+  ) {
+    let controls = self.processor.get_controls(ports);
+
+    let trigger_cv_output = ports.trigger_cv_output.iter();
+    let repeat_trigger_cv_output = ports.repeat_trigger_cv_output.iter();
+
+    for (index, ((((input, output), sidechain), trigger_cv_output), repeat_trigger_cv_output)) in
+      ports
+        .input
+        .iter()
+        .zip(ports.output.iter())
+        .zip(ports.sidechain_input.iter())
+        .zip(trigger_cv_output)
+        .zip(repeat_trigger_cv_output)
+        .enumerate()
+    {
+      let ([stutter_output], trigger_cv, repeat_trigger_cv) = self.processor.process(
+        [input.get()],
+        sidechain.get(),
+        &controls,
+        ports.cv_inputs(index),
       );
-      output_left.set(stutter_output.0);
-      output_right.set(stutter_output.1);
-      trigger_cv_output.set(if stutter_output.2 { 10. } else { 0. });
-      repeat_trigger_cv_output.set(if stutter_output.3 { 10. } else { 0. });
+      output.set(stutter_output);
+      trigger_cv_output.set(trigger_cv);
+      repeat_trigger_cv_output.set(repeat_trigger_cv);
+    }
+  }
+}
+
+#[uri("https://github.com/davemollen/dm-Stutter#mono-to-stereo")]
+struct DmStutterMonoToStereo {
+  processor: StutterProcessor<2>,
+}
+
+impl Plugin for DmStutterMonoToStereo {
+  type Ports = MonoToStereoPorts;
+  type InitFeatures = InitFeatures<'static>;
+  type AudioFeatures = ();
+
+  fn new(plugin_info: &PluginInfo, features: &mut Self::InitFeatures) -> Option<Self> {
+    Some(Self {
+      processor: StutterProcessor::new(plugin_info, features)?,
+    })
+  }
+
+  fn run(
+    &mut self,
+    ports: &mut MonoToStereoPorts,
+    _features: &mut Self::AudioFeatures,
+    _sample_count: u32,
+  ) {
+    let controls = self.processor.get_controls(ports);
+
+    let output_channels = ports.output_left.iter().zip(ports.output_right.iter());
+    let trigger_cv_output = ports.trigger_cv_output.iter();
+    let repeat_trigger_cv_output = ports.repeat_trigger_cv_output.iter();
+
+    for (
+      index,
+      (
+        (((input, (output_left, output_right)), sidechain), trigger_cv_output),
+        repeat_trigger_cv_output,
+      ),
+    ) in ports
+      .input
+      .iter()
+      .zip(output_channels)
+      .zip(ports.sidechain_input.iter())
+      .zip(trigger_cv_output)
+      .zip(repeat_trigger_cv_output)
+      .enumerate()
+    {
+      let ([stutter_output_left, stutter_output_right], trigger_cv, repeat_trigger_cv) =
+        self.processor.process(
+          [input.get(); 2],
+          sidechain.get(),
+          &controls,
+          ports.cv_inputs(index),
+        );
+      output_left.set(stutter_output_left);
+      output_right.set(stutter_output_right);
+      trigger_cv_output.set(trigger_cv);
+      repeat_trigger_cv_output.set(repeat_trigger_cv);
     }
   }
 }
 
 // Generate the plugin descriptor function which exports the plugin to the outside world.
-lv2_descriptors!(DmStutter);
+lv2_descriptors!(DmStutter, DmStutterMono, DmStutterMonoToStereo);
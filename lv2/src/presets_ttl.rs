@@ -0,0 +1,71 @@
+use std::{env, fmt::Write, fs, path::Path};
+use stutter::presets::FACTORY_PRESETS;
+
+const PLUGIN_URIS: [&str; 3] = [
+  "https://github.com/davemollen/dm-Stutter",
+  "https://github.com/davemollen/dm-Stutter#mono",
+  "https://github.com/davemollen/dm-Stutter#mono-to-stereo",
+];
+
+// Generates presets.ttl in the bundle from the factory presets, so the LV2 presets never drift
+// from the presets of the other plugin formats. The test below fails when the committed file is
+// out of date. Run it with UPDATE_PRESETS=1 to write the file again.
+fn get_presets_ttl() -> String {
+  let mut ttl = String::from(
+    "@prefix lv2:  <http://lv2plug.in/ns/lv2core#> .\n\
+     @prefix pset: <http://lv2plug.in/ns/ext/presets#> .\n\
+     @prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .\n",
+  );
+
+  for preset in FACTORY_PRESETS {
+    let plugin_uris = PLUGIN_URIS.map(|uri| format!("<{uri}>")).join(" , ");
+    let ports = preset
+      .get_values()
+      .into_iter()
+      .map(|(symbol, value)| {
+        // The mix port counts from one instead of zero
+        let value = if symbol == "mix" { value + 1. } else { value };
+        format!("\t\tlv2:symbol \"{symbol}\" ;\n\t\tpset:value {value:?}\n")
+      })
+      .collect::<Vec<_>>()
+      .join("\t] , [\n");
+
+    write!(
+      ttl,
+      "\n<{}#preset-{}>\n\
+       \ta pset:Preset ;\n\
+       \tlv2:appliesTo {plugin_uris} ;\n\
+       \trdfs:label \"{}\" ;\n\
+       \tlv2:port [\n{ports}\t] .\n",
+      PLUGIN_URIS[0],
+      get_slug(preset.name),
+      preset.name
+    )
+    .unwrap();
+  }
+
+  ttl
+}
+
+fn get_slug(name: &str) -> String {
+  name
+    .to_lowercase()
+    .split(|character: char| !character.is_ascii_alphanumeric())
+    .filter(|word| !word.is_empty())
+    .collect::<Vec<_>>()
+    .join("-")
+}
+
+#[test]
+fn presets_ttl_should_match_the_factory_presets() {
+  let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("dm-Stutter.lv2/presets.ttl");
+  let presets_ttl = get_presets_ttl();
+  if env::var_os("UPDATE_PRESETS").is_some() {
+    fs::write(&path, &presets_ttl).unwrap();
+  }
+
+  assert!(
+    fs::read_to_string(&path).unwrap() == presets_ttl,
+    "presets.ttl is out of date, run `UPDATE_PRESETS=1 cargo test` in the lv2 folder"
+  );
+}
@@ -1,3 +1,10 @@
+#[path = "./editor/components/midi_learn_modifier.rs"]
+mod midi_learn_modifier;
+#[path = "./editor/components/page_tabs.rs"]
+mod page_tabs;
+#[path = "./editor/components/param_control.rs"]
+mod param_control;
+use page_tabs::{Page, PageTabs};
 #[path = "./editor/components/param_knob.rs"]
 mod param_knob;
 use param_knob::{ParamKnob, ParamKnobSize};
@@ -13,6 +20,26 @@ use param_slider::ParamSlider;
 #[path = "./editor/components/param_radio_button.rs"]
 mod param_radio_button;
 use param_radio_button::ParamRadioButton;
+#[path = "./editor/components/preset_browser.rs"]
+mod preset_browser;
+use preset_browser::PresetBrowser;
+#[path = "./editor/components/probability_actions.rs"]
+mod probability_actions;
+use probability_actions::ProbabilityActions;
+#[path = "./editor/components/probability_graph.rs"]
+mod probability_graph;
+use probability_graph::ProbabilityGraph;
+#[path = "./editor/components/scale_selector.rs"]
+mod scale_selector;
+use scale_selector::ScaleSelector;
+#[path = "./editor/components/slice_view.rs"]
+mod slice_view;
+use slice_view::SliceView;
+#[path = "./editor/components/trigger_history.rs"]
+mod trigger_history;
+use trigger_history::TriggerHistory;
+mod theme;
+mod tooltips;
 mod ui_data;
 use nih_plug::params::Param;
 use nih_plug::prelude::{Editor, Enum};
@@ -21,17 +48,30 @@ use nih_plug_vizia::vizia::{
   layout::Units::Auto,
   model::Model,
   modifiers::{LayoutModifiers, StyleModifiers, TextModifiers},
-  prelude::Units::{Pixels, Stretch},
+  prelude::{
+    EmitContext,
+    Units::{Pixels, Stretch},
+    CSS,
+  },
   style::FontWeightKeyword,
-  views::{Element, HStack, Label, VStack},
+  views::{Button, HStack, Label, VStack},
 };
-use nih_plug_vizia::{create_vizia_editor, vizia_assets, ViziaState, ViziaTheming};
-use std::sync::Arc;
+use nih_plug_vizia::{
+  create_vizia_editor, vizia_assets, widgets::ResizeHandle, ViziaState, ViziaTheming,
+};
+use std::{collections::HashSet, sync::Arc};
+use stutter::{note_values::get_note_value_name, tempo_factor::SORTED_TEMPO_FACTORS};
+use theme::{get_user_stylesheet_path, Theme};
 use ui_data::{ParamChangeEvent, UiData};
 
-use crate::stutter_parameters::{Mix, StutterParameters};
+use crate::{
+  forced_fraction::ForcedFraction,
+  stutter_parameters::{Mix, PanPattern, StutterParameters},
+  visualizer::VisualizerData,
+};
 
 const STYLE: &str = include_str!("./editor/style.css");
+const THEMES: &str = include_str!("./editor/themes.css");
 
 // Makes sense to also define this here, makes it a bit easier to keep track of
 pub(crate) fn default_state() -> Arc<ViziaState> {
@@ -40,26 +80,104 @@ pub(crate) fn default_state() -> Arc<ViziaState> {
 
 pub(crate) fn create(
   params: Arc<StutterParameters>,
+  visualizer_data: Arc<VisualizerData>,
+  forced_fraction: Arc<ForcedFraction>,
   editor_state: Arc<ViziaState>,
 ) -> Option<Box<dyn Editor>> {
   create_vizia_editor(
-    editor_state,
+    editor_state.clone(),
     ViziaTheming::Custom,
     move |cx, gui_context| {
       vizia_assets::register_roboto(cx);
       vizia_assets::register_roboto_bold(cx);
       cx.set_default_font(&[vizia_assets::ROBOTO]);
       cx.add_stylesheet(STYLE).ok();
+      cx.add_stylesheet(THEMES).ok();
+      // The user stylesheet is added last, so it overrides the built-in styles
+      if let Some(path) = get_user_stylesheet_path().filter(|path| path.is_file()) {
+        cx.add_stylesheet(CSS::from_file(path)).ok();
+      }
+
+      params.midi_learn.set_gui_context(gui_context.clone());
 
       UiData {
         params: params.clone(),
         gui_context: gui_context.clone(),
+        visualizer_data: visualizer_data.clone(),
+        forced_fraction: forced_fraction.clone(),
+        preset_index: None,
+        probability_constraint: 0,
+        mutate_amount: 0.25,
+        probability_undo: None,
+        scale_factor: editor_state.scale_factor(),
+        editing_param: None,
+        keys_down: HashSet::new(),
+        theme: Theme::from_class(&params.theme.read().unwrap()),
+        page: Page::Stutter,
       }
       .build(cx);
 
-      HStack::new(cx, |cx| {
+      VStack::new(cx, |cx| {
+        HStack::new(cx, |cx| {
+          Label::new(cx, "Stutter")
+            .font_size(22.0)
+            .font_weight(FontWeightKeyword::Bold)
+            .class("title")
+            .border_radius(Pixels(16.0))
+            .border_width(Pixels(1.))
+            .child_space(Stretch(1.0))
+            .child_top(Pixels(1.0))
+            .child_bottom(Pixels(5.0))
+            .width(Pixels(112.0));
+
+          PresetBrowser::new(cx, UiData::preset_index, |index| {
+            ParamChangeEvent::LoadPreset(index)
+          });
+
+          ScaleSelector::new(cx, UiData::scale_factor, |scale_factor| {
+            ParamChangeEvent::SetScaleFactor(scale_factor)
+          });
+
+          Button::new(
+            cx,
+            |cx| {
+              let theme = UiData::theme.get(cx);
+              cx.emit(ParamChangeEvent::SetTheme(theme.next()));
+            },
+            |cx| Label::new(cx, UiData::theme.map(|theme| theme.get_name())).font_size(12.0),
+          )
+          .height(Pixels(20.0))
+          .width(Pixels(96.0))
+          .child_space(Stretch(1.0));
+        })
+        .width(Stretch(1.0))
+        .height(Auto)
+        .child_top(Stretch(1.0))
+        .child_bottom(Stretch(1.0))
+        .col_between(Stretch(1.0));
+
+        PageTabs::new(cx);
+
         VStack::new(cx, |cx| {
           HStack::new(cx, |cx| {
+            ParamCheckbox::new(
+              cx,
+              params.on.name(),
+              UiData::params,
+              params.on.as_ptr(),
+              |params| &params.on,
+              |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
+            );
+
+            ParamTrigger::new(
+              cx,
+              params.trigger.name(),
+              UiData::params,
+              params.trigger.as_ptr(),
+              |params| &params.trigger,
+              |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
+            );
+
             ParamCheckbox::new(
               cx,
               params.auto.name(),
@@ -78,6 +196,15 @@ pub(crate) fn create(
               |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
             );
 
+            ParamCheckbox::new(
+              cx,
+              params.clock.name(),
+              UiData::params,
+              params.clock.as_ptr(),
+              |params| &params.clock,
+              |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
+            );
+
             ParamRadioButton::new(
               cx,
               params.mix.name(),
@@ -88,14 +215,77 @@ pub(crate) fn create(
               Mix::variants(),
             );
           })
-          .size(Auto)
-          .child_top(Stretch(1.0))
-          .col_between(Pixels(24.0))
-          .child_bottom(Pixels(8.0));
+          .width(Stretch(1.0))
+          .height(Auto)
+          .col_between(Stretch(1.0));
 
           HStack::new(cx, |cx| {
-            // show when sync is on
+            ParamCheckbox::new(
+              cx,
+              params.free_ratio.name(),
+              UiData::params,
+              params.free_ratio.as_ptr(),
+              |params| &params.free_ratio,
+              |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
+            )
+            .disabled(UiData::params.map(|p| !p.sync.value() && !p.clock.value()));
+
+            ParamCheckbox::new(
+              cx,
+              params.stretch.name(),
+              UiData::params,
+              params.stretch.as_ptr(),
+              |params| &params.stretch,
+              |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
+            );
+
+            ParamCheckbox::new(
+              cx,
+              params.freeze.name(),
+              UiData::params,
+              params.freeze.as_ptr(),
+              |params| &params.freeze,
+              |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
+            );
+
             ParamKnob::new(
+              cx,
+              params.pan_pattern.name(),
+              UiData::params,
+              params.pan_pattern.as_ptr(),
+              |params| &params.pan_pattern,
+              |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
+              ParamKnobSize::Regular,
+            );
+
+            ParamKnob::new(
+              cx,
+              params.pan_amount.name(),
+              UiData::params,
+              params.pan_amount.as_ptr(),
+              |params| &params.pan_amount,
+              |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
+              ParamKnobSize::Regular,
+            )
+            .disabled(UiData::params.map(|params| params.pan_pattern.value() == PanPattern::Off));
+
+            ParamKnob::new(
+              cx,
+              params.width.name(),
+              UiData::params,
+              params.width.as_ptr(),
+              |params| &params.width,
+              |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
+              ParamKnobSize::Regular,
+            );
+          })
+          .width(Stretch(1.0))
+          .height(Auto)
+          .col_between(Stretch(1.0));
+
+          HStack::new(cx, |cx| {
+            // show when sync or clock is on
+            ParamKnob::with_order(
               cx,
               params.tempo_factor.name(),
               UiData::params,
@@ -103,11 +293,61 @@ pub(crate) fn create(
               |params| &params.tempo_factor,
               |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
               ParamKnobSize::Regular,
+              Some(&SORTED_TEMPO_FACTORS),
             )
             .class("show")
-            .toggle_class("hide", UiData::params.map(|p| !p.sync.value()));
+            .toggle_class(
+              "hide",
+              UiData::params.map(|p| (!p.sync.value() && !p.clock.value()) || p.free_ratio.value()),
+            );
+
+            // show when sync or clock is on
+            ParamKnob::new(
+              cx,
+              params.sync_mode.name(),
+              UiData::params,
+              params.sync_mode.as_ptr(),
+              |params| &params.sync_mode,
+              |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
+              ParamKnobSize::Regular,
+            )
+            .class("show")
+            .toggle_class(
+              "hide",
+              UiData::params.map(|p| !p.sync.value() && !p.clock.value()),
+            );
 
-            // show when sync is off
+            // show when clock is on
+            ParamKnob::new(
+              cx,
+              params.ppqn.name(),
+              UiData::params,
+              params.ppqn.as_ptr(),
+              |params| &params.ppqn,
+              |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
+              ParamKnobSize::Regular,
+            )
+            .class("show")
+            .toggle_class("hide", UiData::params.map(|p| !p.clock.value()));
+
+            // show when sync or clock is on and free ratio is on
+            ParamKnob::new(
+              cx,
+              params.ratio.name(),
+              UiData::params,
+              params.ratio.as_ptr(),
+              |params| &params.ratio,
+              |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
+              ParamKnobSize::Regular,
+            )
+            .class("show")
+            .toggle_class(
+              "hide",
+              UiData::params
+                .map(|p| (!p.sync.value() && !p.clock.value()) || !p.free_ratio.value()),
+            );
+
+            // show when sync and clock are off
             ParamKnob::new(
               cx,
               params.pulse.name(),
@@ -118,7 +358,25 @@ pub(crate) fn create(
               ParamKnobSize::Regular,
             )
             .class("show")
-            .toggle_class("hide", UiData::params.map(|p| p.sync.value()));
+            .toggle_class(
+              "hide",
+              UiData::params.map(|p| p.sync.value() || p.clock.value()),
+            );
+
+            // show when sync and clock are off
+            ParamTrigger::new(
+              cx,
+              params.tap.name(),
+              UiData::params,
+              params.tap.as_ptr(),
+              |params| &params.tap,
+              |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
+            )
+            .class("show")
+            .toggle_class(
+              "hide",
+              UiData::params.map(|p| p.sync.value() || p.clock.value()),
+            );
 
             ParamKnob::new(
               cx,
@@ -142,199 +400,398 @@ pub(crate) fn create(
             )
             .disabled(UiData::params.map(|params| !params.auto.value()));
           })
-          .size(Auto);
+          .width(Stretch(1.0))
+          .height(Auto)
+          .col_between(Stretch(1.0));
+
+          VStack::new(cx, |cx| {
+            Label::new(
+              cx,
+              UiData::visualizer_data.map(|data| {
+                if data.is_active() {
+                  format!(
+                    "{}  x{}",
+                    get_note_value_name(data.get_time_fraction()),
+                    data.get_repeat_count() + 1
+                  )
+                } else {
+                  String::new()
+                }
+              }),
+            )
+            .font_size(13.0)
+            .left(Stretch(1.0))
+            .height(Pixels(16.0));
+
+            SliceView::new(cx, UiData::visualizer_data)
+              .width(Stretch(1.0))
+              .height(Stretch(1.0))
+              .bottom(Pixels(4.0));
+
+            TriggerHistory::new(cx, UiData::visualizer_data).width(Stretch(1.0));
+          })
+          .size(Stretch(1.0));
+        })
+        .size(Stretch(1.0))
+        .row_between(Pixels(8.0))
+        .class("show")
+        .toggle_class("hide", UiData::page.map(|page| *page != Page::Stutter));
 
+        VStack::new(cx, |cx| {
           HStack::new(cx, |cx| {
             ParamCheckbox::new(
               cx,
-              params.on.name(),
+              params.sidechain.name(),
               UiData::params,
-              params.on.as_ptr(),
-              |params| &params.on,
+              params.sidechain.as_ptr(),
+              |params| &params.sidechain,
+              |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
+            );
+
+            ParamKnob::new(
+              cx,
+              params.sensitivity.name(),
+              UiData::params,
+              params.sensitivity.as_ptr(),
+              |params| &params.sensitivity,
+              |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
+              ParamKnobSize::Regular,
+            )
+            .disabled(UiData::params.map(|params| !params.sidechain.value()));
+
+            ParamKnob::new(
+              cx,
+              params.hold_off.name(),
+              UiData::params,
+              params.hold_off.as_ptr(),
+              |params| &params.hold_off,
+              |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
+              ParamKnobSize::Regular,
+            )
+            .disabled(UiData::params.map(|params| !params.sidechain.value()));
+
+            ParamKnob::new(
+              cx,
+              params.quantize.name(),
+              UiData::params,
+              params.quantize.as_ptr(),
+              |params| &params.quantize,
+              |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
+              ParamKnobSize::Regular,
+            );
+
+            ParamKnob::new(
+              cx,
+              params.quantize_sensitivity.name(),
+              UiData::params,
+              params.quantize_sensitivity.as_ptr(),
+              |params| &params.quantize_sensitivity,
+              |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
+              ParamKnobSize::Regular,
+            )
+            .disabled(UiData::params.map(|params| params.quantize.value() == 0.));
+          })
+          .width(Stretch(1.0))
+          .height(Stretch(1.0))
+          .col_between(Stretch(1.0));
+
+          HStack::new(cx, |cx| {
+            ParamKnob::new(
+              cx,
+              params.forced_note.name(),
+              UiData::params,
+              params.forced_note.as_ptr(),
+              |params| &params.forced_note,
+              |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
+              ParamKnobSize::Small,
+            );
+
+            ParamKnob::new(
+              cx,
+              params.forced_repeats.name(),
+              UiData::params,
+              params.forced_repeats.as_ptr(),
+              |params| &params.forced_repeats,
               |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
+              ParamKnobSize::Small,
             );
 
             ParamTrigger::new(
               cx,
-              params.trigger.name(),
+              params.forced_trigger.name(),
               UiData::params,
-              params.trigger.as_ptr(),
-              |params| &params.trigger,
+              params.forced_trigger.as_ptr(),
+              |params| &params.forced_trigger,
               |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
             );
+
+            ParamCheckbox::new(
+              cx,
+              params.note_triggers.name(),
+              UiData::params,
+              params.note_triggers.as_ptr(),
+              |params| &params.note_triggers,
+              |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
+            );
+
+            ParamKnob::new(
+              cx,
+              params.note_channel.name(),
+              UiData::params,
+              params.note_channel.as_ptr(),
+              |params| &params.note_channel,
+              |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
+              ParamKnobSize::Small,
+            )
+            .disabled(UiData::params.map(|params| !params.note_triggers.value()));
           })
-          .col_between(Pixels(16.0))
-          .size(Auto);
+          .width(Stretch(1.0))
+          .height(Stretch(1.0))
+          .col_between(Stretch(1.0));
 
           HStack::new(cx, |cx| {
-            Label::new(cx, "Stutter")
-              .font_size(22.0)
-              .font_weight(FontWeightKeyword::Bold)
-              .border_radius(Pixels(16.0))
-              .border_width(Pixels(1.))
-              .border_color("#2c5494")
-              .background_color("#3c6ab5")
-              .child_space(Stretch(1.0))
-              .child_top(Pixels(1.0))
-              .child_bottom(Pixels(5.0))
-              .width(Pixels(112.0));
+            ParamKnob::new(
+              cx,
+              params.trigger_note.name(),
+              UiData::params,
+              params.trigger_note.as_ptr(),
+              |params| &params.trigger_note,
+              |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
+              ParamKnobSize::Regular,
+            );
+
+            ParamKnob::new(
+              cx,
+              params.repeat_note.name(),
+              UiData::params,
+              params.repeat_note.as_ptr(),
+              |params| &params.repeat_note,
+              |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
+              ParamKnobSize::Regular,
+            );
+
+            ParamKnob::new(
+              cx,
+              params.note_velocity.name(),
+              UiData::params,
+              params.note_velocity.as_ptr(),
+              |params| &params.note_velocity,
+              |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
+              ParamKnobSize::Regular,
+            );
           })
-          .top(Pixels(32.0))
-          .size(Auto);
+          .width(Stretch(1.0))
+          .height(Stretch(1.0))
+          .col_between(Stretch(1.0));
         })
-        .height(Auto)
+        .size(Stretch(1.0))
         .row_between(Pixels(8.0))
-        .child_space(Stretch(1.0))
-        .top(Stretch(1.0));
+        .class("show")
+        .toggle_class("hide", UiData::page.map(|page| *page != Page::Triggers));
 
-        HStack::new(cx, |cx| {
-          Element::new(cx)
-            .width(Pixels(3.0))
-            .top(Pixels(32.0))
-            .height(Stretch(1.0))
-            .right(Pixels(8.0))
-            .background_color("#363636");
+        VStack::new(cx, |cx| {
+          ProbabilityActions::new(cx);
 
-          VStack::new(cx, |cx| {
-            Label::new(cx, "Note probability")
-              .font_size(16.0)
-              .font_weight(FontWeightKeyword::SemiBold)
-              .left(Stretch(1.0))
-              .bottom(Pixels(8.0));
-
-            HStack::new(cx, |cx| {
-              ParamSlider::new(
-                cx,
-                params.half_notes.name(),
-                UiData::params,
-                params.half_notes.as_ptr(),
-                |params| &params.half_notes,
-                |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
-              );
-              ParamSlider::new(
-                cx,
-                params.seven_sixteenth_notes.name(),
-                UiData::params,
-                params.seven_sixteenth_notes.as_ptr(),
-                |params| &params.seven_sixteenth_notes,
-                |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
-              );
-              ParamSlider::new(
-                cx,
-                params.six_sixteenth_notes.name(),
-                UiData::params,
-                params.six_sixteenth_notes.as_ptr(),
-                |params| &params.six_sixteenth_notes,
-                |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
-              );
-              ParamSlider::new(
-                cx,
-                params.half_triplet_notes.name(),
-                UiData::params,
-                params.half_triplet_notes.as_ptr(),
-                |params| &params.half_triplet_notes,
-                |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
-              );
-              ParamSlider::new(
-                cx,
-                params.five_sixteenth_notes.name(),
-                UiData::params,
-                params.five_sixteenth_notes.as_ptr(),
-                |params| &params.five_sixteenth_notes,
-                |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
-              );
-            });
-
-            HStack::new(cx, |cx| {
-              ParamSlider::new(
-                cx,
-                params.quarter_notes.name(),
-                UiData::params,
-                params.quarter_notes.as_ptr(),
-                |params| &params.quarter_notes,
-                |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
-              );
-              ParamSlider::new(
-                cx,
-                params.three_sixteenth_notes.name(),
-                UiData::params,
-                params.three_sixteenth_notes.as_ptr(),
-                |params| &params.three_sixteenth_notes,
-                |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
-              );
-              ParamSlider::new(
-                cx,
-                params.quarter_triplet_notes.name(),
-                UiData::params,
-                params.quarter_triplet_notes.as_ptr(),
-                |params| &params.quarter_triplet_notes,
-                |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
-              );
-              ParamSlider::new(
-                cx,
-                params.eighth_notes.name(),
-                UiData::params,
-                params.eighth_notes.as_ptr(),
-                |params| &params.eighth_notes,
-                |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
-              );
-              ParamSlider::new(
-                cx,
-                params.eighth_triplet_notes.name(),
-                UiData::params,
-                params.eighth_triplet_notes.as_ptr(),
-                |params| &params.eighth_triplet_notes,
-                |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
-              );
-            });
-
-            HStack::new(cx, |cx| {
-              ParamSlider::new(
-                cx,
-                params.sixteenth_notes.name(),
-                UiData::params,
-                params.sixteenth_notes.as_ptr(),
-                |params| &params.sixteenth_notes,
-                |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
-              );
-              ParamSlider::new(
-                cx,
-                params.sixteenth_triplet_notes.name(),
-                UiData::params,
-                params.sixteenth_triplet_notes.as_ptr(),
-                |params| &params.sixteenth_triplet_notes,
-                |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
-              );
-              ParamSlider::new(
-                cx,
-                params.thirty_second_notes.name(),
-                UiData::params,
-                params.thirty_second_notes.as_ptr(),
-                |params| &params.thirty_second_notes,
-                |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
-              );
-              ParamSlider::new(
-                cx,
-                params.thirty_second_triplet_notes.name(),
-                UiData::params,
-                params.thirty_second_triplet_notes.as_ptr(),
-                |params| &params.thirty_second_triplet_notes,
-                |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
-              );
-              ParamSlider::new(
-                cx,
-                params.sixty_fourth_notes.name(),
-                UiData::params,
-                params.sixty_fourth_notes.as_ptr(),
-                |params| &params.sixty_fourth_notes,
-                |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
-              );
-            });
-          });
-        });
+          HStack::new(cx, |cx| {
+            ProbabilityGraph::new(cx, UiData::params, &params, |param_ptr, val| {
+              ParamChangeEvent::SetParam(param_ptr, val)
+            })
+            .width(Stretch(1.0))
+            .height(Stretch(1.0));
+
+            ParamKnob::new(
+              cx,
+              params.triplet_balance.name(),
+              UiData::params,
+              params.triplet_balance.as_ptr(),
+              |params| &params.triplet_balance,
+              |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
+              ParamKnobSize::Regular,
+            );
+            ParamKnob::new(
+              cx,
+              params.odd_balance.name(),
+              UiData::params,
+              params.odd_balance.as_ptr(),
+              |params| &params.odd_balance,
+              |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
+              ParamKnobSize::Regular,
+            );
+            ParamKnob::new(
+              cx,
+              params.tilt.name(),
+              UiData::params,
+              params.tilt.as_ptr(),
+              |params| &params.tilt,
+              |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
+              ParamKnobSize::Regular,
+            );
+          })
+          .width(Stretch(1.0))
+          .height(Auto)
+          .col_between(Pixels(8.0));
+
+          HStack::new(cx, |cx| {
+            ParamSlider::new(
+              cx,
+              params.half_notes.name(),
+              UiData::params,
+              params.half_notes.as_ptr(),
+              |params| &params.half_notes,
+              |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
+            );
+
+            ParamSlider::new(
+              cx,
+              params.seven_sixteenth_notes.name(),
+              UiData::params,
+              params.seven_sixteenth_notes.as_ptr(),
+              |params| &params.seven_sixteenth_notes,
+              |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
+            );
+
+            ParamSlider::new(
+              cx,
+              params.six_sixteenth_notes.name(),
+              UiData::params,
+              params.six_sixteenth_notes.as_ptr(),
+              |params| &params.six_sixteenth_notes,
+              |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
+            );
+
+            ParamSlider::new(
+              cx,
+              params.half_triplet_notes.name(),
+              UiData::params,
+              params.half_triplet_notes.as_ptr(),
+              |params| &params.half_triplet_notes,
+              |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
+            );
+
+            ParamSlider::new(
+              cx,
+              params.five_sixteenth_notes.name(),
+              UiData::params,
+              params.five_sixteenth_notes.as_ptr(),
+              |params| &params.five_sixteenth_notes,
+              |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
+            );
+
+            ParamSlider::new(
+              cx,
+              params.quarter_notes.name(),
+              UiData::params,
+              params.quarter_notes.as_ptr(),
+              |params| &params.quarter_notes,
+              |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
+            );
+
+            ParamSlider::new(
+              cx,
+              params.three_sixteenth_notes.name(),
+              UiData::params,
+              params.three_sixteenth_notes.as_ptr(),
+              |params| &params.three_sixteenth_notes,
+              |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
+            );
+
+            ParamSlider::new(
+              cx,
+              params.quarter_triplet_notes.name(),
+              UiData::params,
+              params.quarter_triplet_notes.as_ptr(),
+              |params| &params.quarter_triplet_notes,
+              |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
+            );
+          })
+          .width(Stretch(1.0))
+          .height(Stretch(1.0))
+          .col_between(Stretch(1.0));
+
+          HStack::new(cx, |cx| {
+            ParamSlider::new(
+              cx,
+              params.eighth_notes.name(),
+              UiData::params,
+              params.eighth_notes.as_ptr(),
+              |params| &params.eighth_notes,
+              |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
+            );
+
+            ParamSlider::new(
+              cx,
+              params.eighth_triplet_notes.name(),
+              UiData::params,
+              params.eighth_triplet_notes.as_ptr(),
+              |params| &params.eighth_triplet_notes,
+              |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
+            );
+
+            ParamSlider::new(
+              cx,
+              params.sixteenth_notes.name(),
+              UiData::params,
+              params.sixteenth_notes.as_ptr(),
+              |params| &params.sixteenth_notes,
+              |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
+            );
+
+            ParamSlider::new(
+              cx,
+              params.sixteenth_triplet_notes.name(),
+              UiData::params,
+              params.sixteenth_triplet_notes.as_ptr(),
+              |params| &params.sixteenth_triplet_notes,
+              |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
+            );
+
+            ParamSlider::new(
+              cx,
+              params.thirty_second_notes.name(),
+              UiData::params,
+              params.thirty_second_notes.as_ptr(),
+              |params| &params.thirty_second_notes,
+              |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
+            );
+
+            ParamSlider::new(
+              cx,
+              params.thirty_second_triplet_notes.name(),
+              UiData::params,
+              params.thirty_second_triplet_notes.as_ptr(),
+              |params| &params.thirty_second_triplet_notes,
+              |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
+            );
+
+            ParamSlider::new(
+              cx,
+              params.sixty_fourth_notes.name(),
+              UiData::params,
+              params.sixty_fourth_notes.as_ptr(),
+              |params| &params.sixty_fourth_notes,
+              |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
+            );
+          })
+          .width(Stretch(1.0))
+          .height(Stretch(1.0))
+          .col_between(Stretch(1.0));
+        })
+        .size(Stretch(1.0))
+        .row_between(Pixels(8.0))
+        .class("show")
+        .toggle_class("hide", UiData::page.map(|page| *page != Page::Probability));
+
+        // The resize handle scales the whole editor, like the scale selector
+        ResizeHandle::new(cx);
       })
+      .row_between(Pixels(8.0))
       .child_space(Pixels(16.0))
-      .background_color("#505050");
+      .class("editor")
+      .toggle_class("light", UiData::theme.map(|theme| *theme == Theme::Light))
+      .toggle_class(
+        "high-contrast",
+        UiData::theme.map(|theme| *theme == Theme::HighContrast),
+      );
     },
   )
 }
@@ -0,0 +1,28 @@
+use super::ui_data::{ParamChangeEvent, UiData};
+use nih_plug::prelude::ParamPtr;
+use nih_plug_vizia::vizia::{
+  input::MouseButton,
+  prelude::{ActionModifiers, EmitContext, LensExt, StyleModifiers},
+  view::{Handle, View},
+};
+
+pub trait MidiLearnModifier {
+  /// Right-clicking the view learns the next MIDI CC for the parameter. Right-clicking again
+  /// while learning removes the mappings of the parameter.
+  fn midi_learn(self, param_ptr: ParamPtr) -> Self;
+}
+
+impl<'a, V: View> MidiLearnModifier for Handle<'a, V> {
+  fn midi_learn(self, param_ptr: ParamPtr) -> Self {
+    self
+      .on_mouse_down(move |cx, button| {
+        if button == MouseButton::Right {
+          cx.emit(ParamChangeEvent::LearnMidi(param_ptr));
+        }
+      })
+      .toggle_class(
+        "learning",
+        UiData::params.map(move |params| params.midi_learn.is_learning(param_ptr)),
+      )
+  }
+}
@@ -0,0 +1,56 @@
+use super::ui_data::{ParamChangeEvent, UiData};
+use nih_plug_vizia::vizia::{
+  prelude::{
+    Context, Data, EmitContext, LayoutModifiers, LensExt, StyleModifiers, TextModifiers,
+    Units::{Pixels, Stretch},
+  },
+  view::Handle,
+  views::{Button, HStack, Label},
+};
+
+/// The editor shows one page of controls at a time, so it fits in a small window.
+#[derive(Clone, Copy, PartialEq, Data)]
+pub enum Page {
+  Stutter,
+  Triggers,
+  Probability,
+}
+
+impl Page {
+  pub const ALL: [Page; 3] = [Page::Stutter, Page::Triggers, Page::Probability];
+
+  pub fn get_name(&self) -> &'static str {
+    match self {
+      Page::Stutter => "Stutter",
+      Page::Triggers => "Triggers",
+      Page::Probability => "Probability",
+    }
+  }
+}
+
+pub struct PageTabs;
+
+impl PageTabs {
+  pub fn new(cx: &mut Context) -> Handle<HStack> {
+    HStack::new(cx, |cx| {
+      Page::ALL.into_iter().for_each(|page| {
+        Button::new(
+          cx,
+          move |cx| cx.emit(ParamChangeEvent::SetPage(page)),
+          move |cx| Label::new(cx, page.get_name()).font_size(12.0),
+        )
+        .width(Stretch(1.0))
+        .height(Pixels(22.0))
+        .child_space(Stretch(1.0))
+        .border_radius(Pixels(2.0))
+        .toggle_class(
+          "selected",
+          UiData::page.map(move |selected| *selected == page),
+        );
+      });
+    })
+    .width(Stretch(1.0))
+    .height(Pixels(22.0))
+    .col_between(Pixels(4.0))
+  }
+}
@@ -0,0 +1,188 @@
+use super::{
+  midi_learn_modifier::MidiLearnModifier,
+  tooltips::get_tooltip,
+  ui_data::{ParamChangeEvent, UiData},
+};
+use nih_plug::prelude::{ParamPtr, Params};
+use nih_plug_vizia::vizia::{
+  binding::LensExt,
+  context::{Context, EmitContext, EventContext},
+  events::{Event, Propagation},
+  input::{Modifiers, MouseButton},
+  layout::{
+    PositionType,
+    Units::{Auto, Percentage, Pixels, Stretch},
+  },
+  modifiers::{LayoutModifiers, StyleModifiers, TextModifiers},
+  view::{Handle, View},
+  views::{Label, TextEvent, Textbox},
+  window::WindowEvent,
+};
+use std::any::Any;
+
+// The share of the range a scroll step moves, and with shift held down
+const SCROLL_STEP: f32 = 0.01;
+const FINE_SCROLL_STEP: f32 = 0.001;
+
+/// Wraps a control of a parameter with the interactions every control shares. Double-click to
+/// type a value, scroll to adjust the value, ctrl-click to reset it and right-click to MIDI learn
+/// it. Hovering shows what the parameter does. Momentary parameters trigger on every change, so
+/// they can't be scrolled or reset.
+pub struct ParamControl<C> {
+  param_ptr: ParamPtr,
+  on_change: C,
+  is_momentary: bool,
+  order: Option<&'static [usize]>,
+}
+
+impl<C, M> ParamControl<C>
+where
+  M: Any + Send,
+  C: 'static + Fn(ParamPtr, f32) -> M + Copy + Send + Sync,
+{
+  pub fn new<'a>(
+    cx: &'a mut Context,
+    param_ptr: ParamPtr,
+    on_change: C,
+    content: impl FnOnce(&mut Context),
+  ) -> Handle<'a, Self> {
+    Self::with_order(cx, param_ptr, on_change, None, content)
+  }
+
+  /// The order lists the plain values of a stepped parameter in the order they're shown, for a
+  /// parameter that stores its values in another order. Scrolling follows the order.
+  pub fn with_order<'a>(
+    cx: &'a mut Context,
+    param_ptr: ParamPtr,
+    on_change: C,
+    order: Option<&'static [usize]>,
+    content: impl FnOnce(&mut Context),
+  ) -> Handle<'a, Self> {
+    let params = UiData::params.get(cx);
+    let param_id = params
+      .param_map()
+      .into_iter()
+      .find_map(|(id, ptr, _)| (ptr == param_ptr).then_some(id));
+
+    Self {
+      param_ptr,
+      on_change,
+      is_momentary: params.get_momentary_params().contains(&param_ptr),
+      order,
+    }
+    .build(cx, |cx| {
+      content(cx);
+
+      Textbox::new(
+        cx,
+        UiData::params.map(move |_| unsafe {
+          param_ptr.normalized_value_to_string(param_ptr.modulated_normalized_value(), true)
+        }),
+      )
+      .class("value-entry")
+      .toggle_class(
+        "hide",
+        UiData::editing_param.map(move |editing_param| *editing_param != Some(param_ptr)),
+      )
+      .on_submit(move |cx, text, success| {
+        cx.emit(TextEvent::EndEdit);
+        if success {
+          if let Some(value) = unsafe { param_ptr.string_to_normalized_value(&text) } {
+            cx.emit(on_change(param_ptr, value));
+          }
+        }
+        cx.emit(ParamChangeEvent::EndValueEntry);
+      })
+      .position_type(PositionType::SelfDirected)
+      .left(Stretch(1.0))
+      .right(Stretch(1.0))
+      .bottom(Pixels(0.0));
+
+      if let Some(tooltip) = param_id.as_deref().and_then(get_tooltip) {
+        Label::new(cx, tooltip)
+          .class("tooltip")
+          .position_type(PositionType::SelfDirected)
+          .top(Percentage(100.0))
+          .width(Pixels(200.0))
+          .height(Auto)
+          .text_wrap(true)
+          .z_index(10)
+          .hoverable(false);
+      }
+    })
+    .midi_learn(param_ptr)
+  }
+
+  fn set_value(&self, cx: &mut EventContext, value: f32) {
+    cx.emit((self.on_change)(self.param_ptr, value.clamp(0., 1.)));
+  }
+}
+
+impl<C, M> View for ParamControl<C>
+where
+  M: Any + Send,
+  C: 'static + Fn(ParamPtr, f32) -> M + Copy + Send + Sync,
+{
+  fn element(&self) -> Option<&'static str> {
+    Some("param-control")
+  }
+
+  fn event(&mut self, cx: &mut EventContext, event: &mut Event) {
+    event.map(|window_event, meta| match window_event {
+      WindowEvent::MouseDown(MouseButton::Left)
+        if !self.is_momentary && cx.modifiers().intersects(Modifiers::CTRL | Modifiers::LOGO) =>
+      {
+        self.set_value(cx, unsafe { self.param_ptr.default_normalized_value() });
+        meta.consume();
+      }
+      WindowEvent::MouseDoubleClick(MouseButton::Left) => {
+        cx.emit(ParamChangeEvent::StartValueEntry(self.param_ptr));
+        // Only the value entry of this control is a textbox
+        for text_event in [TextEvent::StartEdit, TextEvent::SelectAll] {
+          cx.emit_custom(
+            Event::new(text_event)
+              .target(cx.current())
+              .propagate(Propagation::Subtree),
+          );
+        }
+        meta.consume();
+      }
+      WindowEvent::MouseScroll(_, y) if !self.is_momentary && *y != 0. => {
+        let value = match self.order {
+          Some(order) => {
+            let value = unsafe { self.param_ptr.modulated_plain_value() };
+            let position = get_position(order, value) + y.signum();
+            get_normalized_value(self.param_ptr, order, position)
+          }
+          None => {
+            // Stepped parameters move one step at a time
+            let step = match unsafe { self.param_ptr.step_count() } {
+              Some(step_count) => 1. / step_count as f32,
+              None if cx.modifiers().contains(Modifiers::SHIFT) => FINE_SCROLL_STEP,
+              None => SCROLL_STEP,
+            };
+            let value = unsafe { self.param_ptr.modulated_normalized_value() };
+            value + y.signum() * step
+          }
+        };
+        self.set_value(cx, value);
+        meta.consume();
+      }
+      _ => {}
+    });
+  }
+}
+
+/// Returns the position of a plain value in the order.
+pub fn get_position(order: &[usize], value: f32) -> f32 {
+  order
+    .iter()
+    .position(|ordered_value| *ordered_value == value.round() as usize)
+    .unwrap_or_default() as f32
+}
+
+/// Returns the normalized value of the parameter at the nearest position in the order.
+pub fn get_normalized_value(param_ptr: ParamPtr, order: &[usize], position: f32) -> f32 {
+  let index = (position.round().max(0.) as usize).min(order.len() - 1);
+  unsafe { param_ptr.preview_normalized(order[index] as f32) }
+}
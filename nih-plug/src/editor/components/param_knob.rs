@@ -1,15 +1,16 @@
+use super::param_control::{get_normalized_value, get_position, ParamControl};
 use nih_plug::prelude::{Param, ParamPtr};
 use nih_plug_vizia::vizia::{
   binding::Lens,
   layout::Units::Auto,
-  modifiers::TextModifiers,
+  modifiers::{StyleModifiers, TextModifiers},
   prelude::{
-    ActionModifiers, Context, EmitContext, LayoutModifiers, LensExt, Units,
+    Context, EmitContext, LayoutModifiers, LensExt, Units,
     Units::{Pixels, Stretch},
   },
   style::FontWeightKeyword,
   view::Handle,
-  views::{Knob, Label, TextEvent, Textbox, VStack},
+  views::{Knob, Label},
 };
 use std::any::Any;
 
@@ -40,7 +41,7 @@ impl ParamKnob {
     params_to_param: F,
     on_change: C,
     size: ParamKnobSize,
-  ) -> Handle<'a, VStack>
+  ) -> Handle<'a, ParamControl<C>>
   where
     L: 'static + Lens + Copy + Send + Sync,
     <L as Lens>::Source: 'static,
@@ -49,48 +50,87 @@ impl ParamKnob {
     M: Any + Send,
     C: 'static + Fn(ParamPtr, f32) -> M + Copy + Send + Sync,
   {
-    VStack::new(cx, |cx| {
+    Self::with_order(
+      cx,
+      name,
+      lens,
+      param_ptr,
+      params_to_param,
+      on_change,
+      size,
+      None,
+    )
+  }
+
+  /// Turns through the plain values of a stepped parameter in the given order, instead of the
+  /// order they're stored in.
+  pub fn with_order<'a, L, P, F, M, C>(
+    cx: &'a mut Context,
+    name: &'a str,
+    lens: L,
+    param_ptr: ParamPtr,
+    params_to_param: F,
+    on_change: C,
+    size: ParamKnobSize,
+    order: Option<&'static [usize]>,
+  ) -> Handle<'a, ParamControl<C>>
+  where
+    L: 'static + Lens + Copy + Send + Sync,
+    <L as Lens>::Source: 'static,
+    P: Param,
+    F: 'static + Fn(&<L as Lens>::Target) -> &P + Copy + Send + Sync,
+    M: Any + Send,
+    C: 'static + Fn(ParamPtr, f32) -> M + Copy + Send + Sync,
+  {
+    ParamControl::with_order(cx, param_ptr, on_change, order, |cx| {
       Label::new(cx, name)
         .font_size(13.0)
         .font_weight(FontWeightKeyword::SemiBold)
         .text_wrap(false)
         .child_space(Stretch(1.0));
 
-      Knob::new(
-        cx,
-        lens.map(move |p| params_to_param(p).default_normalized_value()),
-        lens.map(move |p| params_to_param(p).modulated_normalized_value()),
-        false,
-      )
-      .on_changing(move |cx, val| {
-        cx.emit(on_change(param_ptr, val));
-      })
-      .size(size.get_value());
+      // The knob turns through the positions in the order instead of the stored values
+      if let Some(order) = order {
+        let last_position = (order.len() - 1) as f32;
+        let default_value =
+          unsafe { param_ptr.preview_plain(param_ptr.default_normalized_value()) };
 
-      Textbox::new(
+        Knob::new(
+          cx,
+          get_position(order, default_value) / last_position,
+          lens.map(move |_| {
+            get_position(order, unsafe { param_ptr.modulated_plain_value() }) / last_position
+          }),
+          false,
+        )
+        .on_changing(move |cx, val| {
+          cx.emit(on_change(
+            param_ptr,
+            get_normalized_value(param_ptr, order, val * last_position),
+          ));
+        })
+        .size(size.get_value());
+      } else {
+        Knob::new(
+          cx,
+          lens.map(move |p| params_to_param(p).default_normalized_value()),
+          lens.map(move |p| params_to_param(p).modulated_normalized_value()),
+          false,
+        )
+        .on_changing(move |cx, val| {
+          cx.emit(on_change(param_ptr, val));
+        })
+        .size(size.get_value());
+      }
+
+      Label::new(
         cx,
         lens.map(move |p| {
           params_to_param(p)
             .normalized_value_to_string(params_to_param(p).modulated_normalized_value(), true)
         }),
       )
-      .on_mouse_down(|cx, _| {
-        cx.emit(TextEvent::StartEdit);
-        cx.emit(TextEvent::Clear);
-      })
-      .on_submit(move |cx, text, success| {
-        cx.emit(TextEvent::EndEdit);
-
-        if success {
-          let val = lens
-            .map(move |p| params_to_param(p).string_to_normalized_value(&text))
-            .get(cx);
-          match val {
-            Some(val) => cx.emit(on_change(param_ptr, val)),
-            _ => (),
-          };
-        }
-      })
+      .class("value")
       .font_size(12.0)
       .top(Pixels(-4.0));
     })
@@ -1,3 +1,4 @@
+use super::param_control::ParamControl;
 use nih_plug::params::{internals::ParamPtr, Param};
 use nih_plug_vizia::vizia::{
   binding::Lens,
@@ -24,7 +25,7 @@ impl ParamRadioButton {
     params_to_param: F,
     on_change: C,
     variants: &'static [&'static str],
-  ) -> Handle<'a, VStack>
+  ) -> Handle<'a, ParamControl<C>>
   where
     L: 'static + Lens + Copy + Send + Sync,
     <L as Lens>::Source: 'static,
@@ -33,7 +34,7 @@ impl ParamRadioButton {
     M: Any + Send,
     C: 'static + Fn(ParamPtr, f32) -> M + Copy + Send + Sync,
   {
-    VStack::new(cx, |cx| {
+    ParamControl::new(cx, param_ptr, on_change, |cx| {
       Label::new(cx, name)
         .font_size(13.0)
         .font_weight(FontWeightKeyword::SemiBold)
@@ -1,3 +1,4 @@
+use super::param_control::ParamControl;
 use std::any::Any;
 
 use nih_plug::{params::Param, prelude::ParamPtr};
@@ -8,7 +9,7 @@ use nih_plug_vizia::vizia::{
   modifiers::{LayoutModifiers, StyleModifiers, TextModifiers},
   style::FontWeightKeyword,
   view::Handle,
-  views::{Label, Slider, VStack},
+  views::{Label, Slider},
 };
 
 pub struct ParamSlider;
@@ -21,7 +22,7 @@ impl ParamSlider {
     param_ptr: ParamPtr,
     params_to_param: F,
     on_change: C,
-  ) -> Handle<'a, VStack>
+  ) -> Handle<'a, ParamControl<C>>
   where
     L: 'static + Lens + Copy + Send + Sync,
     <L as Lens>::Source: 'static,
@@ -30,7 +31,7 @@ impl ParamSlider {
     M: Any + Send,
     C: 'static + Fn(ParamPtr, f32) -> M + Copy + Send + Sync,
   {
-    VStack::new(cx, |cx| {
+    ParamControl::new(cx, param_ptr, on_change, |cx| {
       Label::new(cx, name)
         .width(Pixels(56.))
         .font_size(13.0)
@@ -45,10 +46,12 @@ impl ParamSlider {
         }),
       )
       .on_changing(move |cx, val| cx.emit(on_change(param_ptr, val)))
-      .height(Pixels(80.))
+      .height(Stretch(1.0))
+      .min_height(Pixels(32.))
       .class("vertical");
     })
-    .size(Auto)
+    .width(Auto)
+    .height(Stretch(1.0))
     .child_space(Stretch(1.0))
     .row_between(Pixels(8.0))
   }
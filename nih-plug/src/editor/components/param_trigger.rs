@@ -1,3 +1,4 @@
+use super::param_control::ParamControl;
 use nih_plug::prelude::{Param, ParamPtr};
 use nih_plug_vizia::vizia::{
   binding::Lens,
@@ -9,7 +10,7 @@ use nih_plug_vizia::vizia::{
   },
   style::FontWeightKeyword,
   view::Handle,
-  views::{Button, Element, Label, VStack},
+  views::{Button, Element, Label},
 };
 use std::any::Any;
 
@@ -23,7 +24,7 @@ impl ParamTrigger {
     param_ptr: ParamPtr,
     params_to_param: F,
     on_change: C,
-  ) -> Handle<'a, VStack>
+  ) -> Handle<'a, ParamControl<C>>
   where
     L: 'static + Lens + Copy + Send + Sync,
     <L as Lens>::Source: 'static,
@@ -32,7 +33,7 @@ impl ParamTrigger {
     M: Any + Send,
     C: 'static + Fn(ParamPtr, f32) -> M + Copy + Send + Sync,
   {
-    VStack::new(cx, |cx| {
+    ParamControl::new(cx, param_ptr, on_change, |cx| {
       Label::new(cx, name)
         .font_size(13.0)
         .font_weight(FontWeightKeyword::SemiBold)
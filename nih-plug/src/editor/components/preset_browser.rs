@@ -0,0 +1,73 @@
+use nih_plug_vizia::vizia::{
+  binding::Lens,
+  layout::Units::Auto,
+  modifiers::TextModifiers,
+  prelude::{
+    Context, EmitContext, LayoutModifiers, LensExt,
+    Units::{Pixels, Stretch},
+  },
+  style::FontWeightKeyword,
+  view::Handle,
+  views::{Button, HStack, Label},
+};
+use std::any::Any;
+use stutter::presets::FACTORY_PRESETS;
+
+/// Steps through the factory presets. nih-plug can't offer them to the host, so this is the only
+/// place the VST3 & CLAP plugins list them.
+pub struct PresetBrowser;
+
+impl PresetBrowser {
+  pub fn new<L, M, C>(cx: &mut Context, lens: L, on_change: C) -> Handle<HStack>
+  where
+    L: 'static + Lens<Target = Option<usize>> + Copy + Send + Sync,
+    <L as Lens>::Source: 'static,
+    M: Any + Send,
+    C: 'static + Fn(usize) -> M + Copy + Send + Sync,
+  {
+    HStack::new(cx, |cx| {
+      Button::new(
+        cx,
+        move |cx| {
+          let index = match lens.get(cx) {
+            Some(index) if index > 0 => index - 1,
+            _ => FACTORY_PRESETS.len() - 1,
+          };
+          cx.emit(on_change(index));
+        },
+        |cx| Label::new(cx, "<"),
+      )
+      .size(Pixels(24.0))
+      .child_space(Stretch(1.0));
+
+      Label::new(
+        cx,
+        lens.map(|index| {
+          index
+            .map(|index| FACTORY_PRESETS[index].name)
+            .unwrap_or("Presets")
+            .to_string()
+        }),
+      )
+      .font_size(13.0)
+      .font_weight(FontWeightKeyword::SemiBold)
+      .width(Pixels(136.0))
+      .child_space(Stretch(1.0));
+
+      Button::new(
+        cx,
+        move |cx| {
+          let index = lens
+            .get(cx)
+            .map_or(0, |index| (index + 1) % FACTORY_PRESETS.len());
+          cx.emit(on_change(index));
+        },
+        |cx| Label::new(cx, ">"),
+      )
+      .size(Pixels(24.0))
+      .child_space(Stretch(1.0));
+    })
+    .size(Auto)
+    .col_between(Pixels(8.0))
+  }
+}
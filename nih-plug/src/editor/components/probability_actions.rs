@@ -0,0 +1,84 @@
+use super::ui_data::{ParamChangeEvent, UiData};
+use nih_plug_vizia::vizia::{
+  layout::Units::Auto,
+  prelude::{
+    ActionModifiers, Context, CursorIcon, EmitContext, EventContext, LayoutModifiers, LensExt,
+    StyleModifiers, TextModifiers,
+    Units::{Pixels, Stretch},
+  },
+  view::Handle,
+  views::{Button, HStack, Label},
+};
+
+const CONSTRAINTS: [&str; 4] = ["All", "Straight", "Triplets", "≤ 1/8"];
+const MUTATE_AMOUNTS: [f32; 3] = [0.1, 0.25, 0.5];
+
+pub struct ProbabilityActions;
+
+impl ProbabilityActions {
+  pub fn new(cx: &mut Context) -> Handle<HStack> {
+    HStack::new(cx, |cx| {
+      CONSTRAINTS
+        .iter()
+        .enumerate()
+        .for_each(|(constraint, name)| {
+          let constraint = constraint as i32;
+
+          Self::button(cx, name, move |cx| {
+            cx.emit(ParamChangeEvent::SetProbabilityConstraint(constraint))
+          })
+          .toggle_class(
+            "selected",
+            UiData::probability_constraint.map(move |selected| *selected == constraint),
+          );
+        });
+
+      Self::button(cx, "Randomize", |cx| {
+        cx.emit(ParamChangeEvent::RandomizeProbability)
+      })
+      .left(Pixels(16.0));
+
+      Self::button(cx, "Mutate", |cx| {
+        cx.emit(ParamChangeEvent::MutateProbability)
+      });
+
+      // Clicking the amount steps through the mutate amounts
+      Label::new(
+        cx,
+        UiData::mutate_amount.map(|amount| format!("± {:.0} %", amount * 100.)),
+      )
+      .font_size(12.0)
+      .width(Pixels(48.0))
+      .child_space(Stretch(1.0))
+      .cursor(CursorIcon::Hand)
+      .on_press(|cx| {
+        let amount = UiData::mutate_amount.get(cx);
+        let index = MUTATE_AMOUNTS
+          .iter()
+          .position(|mutate_amount| *mutate_amount == amount)
+          .map_or(0, |index| (index + 1) % MUTATE_AMOUNTS.len());
+        cx.emit(ParamChangeEvent::SetMutateAmount(MUTATE_AMOUNTS[index]));
+      });
+
+      Self::button(cx, "Undo", |cx| cx.emit(ParamChangeEvent::UndoProbability))
+        .disabled(UiData::probability_undo.map(|undo| undo.is_none()));
+    })
+    .size(Auto)
+    .left(Stretch(1.0))
+    .col_between(Pixels(4.0))
+  }
+
+  fn button<'a>(
+    cx: &'a mut Context,
+    name: &'static str,
+    action: impl 'static + Fn(&mut EventContext) + Send + Sync,
+  ) -> Handle<'a, Button> {
+    Button::new(cx, action, move |cx| Label::new(cx, name).font_size(12.0))
+      .height(Pixels(22.0))
+      .width(Auto)
+      .child_space(Stretch(1.0))
+      .child_left(Pixels(6.0))
+      .child_right(Pixels(6.0))
+      .border_radius(Pixels(2.0))
+  }
+}
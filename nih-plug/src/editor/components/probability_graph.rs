@@ -0,0 +1,138 @@
+use crate::stutter_parameters::StutterParameters;
+use nih_plug::prelude::{Param, ParamPtr};
+use nih_plug_vizia::vizia::{
+  binding::{Binding, Lens, LensExt},
+  context::{Context, EmitContext, EventContext},
+  events::Event,
+  input::MouseButton,
+  layout::{
+    LayoutType, PositionType,
+    Units::{Percentage, Pixels, Stretch},
+  },
+  modifiers::{LayoutModifiers, StyleModifiers},
+  view::{Handle, View},
+  views::{Element, VStack},
+  window::WindowEvent,
+};
+use std::{any::Any, array, sync::Arc};
+use stutter::{note_values::NOTE_VALUES, time_fraction_generator::TimeFractionGenerator};
+
+/// Shows the chance of every note value, after the probabilities are normalized and biased by
+/// the macros, like the stutter picks them. When every probability is zero, the stutter picks
+/// from all notes evenly, which shows as equal bars. The bars are scaled to the most likely note.
+/// The line in every bar is the probability itself, which can be drawn with the mouse.
+pub struct ProbabilityGraph<C> {
+  param_ptrs: [ParamPtr; 15],
+  on_change: C,
+  is_drawing: bool,
+}
+
+impl<C, M> ProbabilityGraph<C>
+where
+  M: Any + Send,
+  C: 'static + Fn(ParamPtr, f32) -> M + Send + Sync,
+{
+  pub fn new<L>(cx: &mut Context, lens: L, params: &StutterParameters, on_change: C) -> Handle<Self>
+  where
+    L: 'static + Lens<Target = Arc<StutterParameters>> + Copy + Send + Sync,
+  {
+    Self {
+      param_ptrs: params.get_probability_params().map(|param| param.as_ptr()),
+      on_change,
+      is_drawing: false,
+    }
+    .build(cx, |cx| {
+      // Every bar depends on all probabilities, so the distribution is computed once for all bars
+      // and the bars are only rebuilt when it changes.
+      Binding::new(cx, lens.map(get_bar_heights), move |cx, bar_heights| {
+        for (index, bar_height) in bar_heights.get(cx).into_iter().enumerate() {
+          VStack::new(cx, |cx| {
+            Element::new(cx)
+              .class("probability-bar")
+              .width(Stretch(1.0))
+              .top(Stretch(1.0))
+              .height(Percentage(bar_height));
+
+            Element::new(cx)
+              .class("probability-weight")
+              .position_type(PositionType::SelfDirected)
+              .width(Stretch(1.0))
+              .height(Pixels(2.0))
+              .top(Stretch(1.0))
+              .bottom(lens.map(move |params| {
+                Percentage(params.get_probability_params()[index].value() * 100.)
+              }));
+          })
+          .width(Stretch(1.0))
+          .height(Stretch(1.0))
+          .hoverable(false);
+        }
+      });
+    })
+    .layout_type(LayoutType::Row)
+    .col_between(Pixels(2.0))
+  }
+
+  // Sets the probability of the bar under the mouse to the height of the mouse in the graph.
+  fn draw_probability(&self, cx: &mut EventContext, x: f32, y: f32) {
+    let bounds = cx.bounds();
+    if bounds.w == 0. || bounds.h == 0. {
+      return;
+    }
+    let index = (((x - bounds.x) / bounds.w * 15.).floor().max(0.) as usize).min(14);
+    let value = (1. - (y - bounds.y) / bounds.h).clamp(0., 1.);
+    cx.emit((self.on_change)(self.param_ptrs[index], value));
+  }
+}
+
+impl<C, M> View for ProbabilityGraph<C>
+where
+  M: Any + Send,
+  C: 'static + Fn(ParamPtr, f32) -> M + Send + Sync,
+{
+  fn element(&self) -> Option<&'static str> {
+    Some("probability-graph")
+  }
+
+  fn event(&mut self, cx: &mut EventContext, event: &mut Event) {
+    event.map(|window_event, meta| match window_event {
+      WindowEvent::MouseDown(MouseButton::Left) => {
+        self.is_drawing = true;
+        cx.capture();
+        self.draw_probability(cx, cx.mouse().cursorx, cx.mouse().cursory);
+        meta.consume();
+      }
+      WindowEvent::MouseMove(x, y) if self.is_drawing => {
+        self.draw_probability(cx, *x, *y);
+      }
+      WindowEvent::MouseUp(MouseButton::Left) => {
+        self.is_drawing = false;
+        cx.release();
+      }
+      _ => {}
+    });
+  }
+}
+
+// Returns the height of every bar in percent, relative to the most likely note.
+fn get_bar_heights(params: &Arc<StutterParameters>) -> Vec<f32> {
+  let probability = params.get_probability_params();
+  let mut time_fraction_generator = TimeFractionGenerator::new();
+  time_fraction_generator.set_bias(
+    params.triplet_balance.value(),
+    params.odd_balance.value(),
+    params.tilt.value(),
+  );
+  time_fraction_generator.set_probability(array::from_fn(|index| {
+    (probability[index].value(), NOTE_VALUES[index].1)
+  }));
+  let distribution = time_fraction_generator.get_distribution();
+  let max = distribution
+    .into_iter()
+    .fold(0., f32::max)
+    .max(f32::EPSILON);
+  distribution
+    .into_iter()
+    .map(|chance| chance / max * 100.)
+    .collect()
+}
@@ -0,0 +1,76 @@
+use nih_plug_vizia::vizia::{
+  binding::Lens,
+  layout::Units::Auto,
+  modifiers::TextModifiers,
+  prelude::{
+    Context, EmitContext, LayoutModifiers, LensExt,
+    Units::{Pixels, Stretch},
+  },
+  view::Handle,
+  views::{Button, HStack, Label},
+};
+use std::any::Any;
+
+pub const SCALE_FACTORS: [f64; 6] = [0.75, 1., 1.25, 1.5, 1.75, 2.];
+
+pub struct ScaleSelector;
+
+impl ScaleSelector {
+  pub fn new<L, M, C>(cx: &mut Context, lens: L, on_change: C) -> Handle<HStack>
+  where
+    L: 'static + Lens<Target = f64> + Copy + Send + Sync,
+    <L as Lens>::Source: 'static,
+    M: Any + Send,
+    C: 'static + Fn(f64) -> M + Copy + Send + Sync,
+  {
+    HStack::new(cx, |cx| {
+      Button::new(
+        cx,
+        move |cx| {
+          let index = get_index(lens.get(cx));
+          cx.emit(on_change(SCALE_FACTORS[index.saturating_sub(1)]));
+        },
+        |cx| Label::new(cx, "-"),
+      )
+      .size(Pixels(20.0))
+      .child_space(Stretch(1.0));
+
+      Label::new(
+        cx,
+        lens.map(|scale_factor| format!("{:.0} %", scale_factor * 100.)),
+      )
+      .font_size(12.0)
+      .width(Pixels(44.0))
+      .child_space(Stretch(1.0));
+
+      Button::new(
+        cx,
+        move |cx| {
+          let index = get_index(lens.get(cx));
+          cx.emit(on_change(
+            SCALE_FACTORS[(index + 1).min(SCALE_FACTORS.len() - 1)],
+          ));
+        },
+        |cx| Label::new(cx, "+"),
+      )
+      .size(Pixels(20.0))
+      .child_space(Stretch(1.0));
+    })
+    .size(Auto)
+    .col_between(Pixels(4.0))
+  }
+}
+
+// Returns the scale factor that is closest to the current one, as the host might have stored a
+// scale factor that isn't in the list.
+fn get_index(scale_factor: f64) -> usize {
+  SCALE_FACTORS
+    .iter()
+    .enumerate()
+    .min_by(|(_, a), (_, b)| {
+      (*a - scale_factor)
+        .abs()
+        .total_cmp(&(*b - scale_factor).abs())
+    })
+    .map_or(1, |(index, _)| index)
+}
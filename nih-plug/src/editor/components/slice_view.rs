@@ -0,0 +1,84 @@
+use crate::visualizer::{VisualizerData, ENVELOPE_LENGTH, ENVELOPE_RESOLUTION};
+use nih_plug_vizia::vizia::{
+  binding::{Binding, Lens, LensExt},
+  context::{Context, DrawContext},
+  style::Color,
+  vg,
+  view::{Canvas, Handle, View},
+};
+use std::sync::Arc;
+
+/// Draws the envelope of the input with the slice that is looping highlighted. The slice is the
+/// audio right before the last trigger, with the length of the current slice. The colours come
+/// from the stylesheet: `background-color`, `color` for the envelope and `selection-color` for the
+/// slice.
+pub struct SliceView {
+  data: Arc<VisualizerData>,
+}
+
+impl SliceView {
+  pub fn new<L>(cx: &mut Context, lens: L) -> Handle<Self>
+  where
+    L: 'static + Lens<Target = Arc<VisualizerData>> + Copy + Send + Sync,
+  {
+    Self { data: lens.get(cx) }.build(cx, |cx| {
+      // Redraw every time the audio thread adds a point to the envelope
+      Binding::new(cx, lens.map(|data| data.get_position()), |cx, _| {
+        cx.needs_redraw()
+      });
+    })
+  }
+}
+
+impl View for SliceView {
+  fn element(&self) -> Option<&'static str> {
+    Some("slice-view")
+  }
+
+  fn draw(&self, cx: &mut DrawContext, canvas: &mut Canvas) {
+    let bounds = cx.bounds();
+    if bounds.w == 0. || bounds.h == 0. {
+      return;
+    }
+    let point_width = bounds.w / ENVELOPE_LENGTH as f32;
+
+    let mut background = vg::Path::new();
+    background.rect(bounds.x, bounds.y, bounds.w, bounds.h);
+    canvas.fill_path(
+      &background,
+      &vg::Paint::color(to_vg_color(cx.background_color())),
+    );
+
+    if self.data.is_active() {
+      let slice_end = ENVELOPE_LENGTH.saturating_sub(self.data.get_points_since_trigger());
+      let slice_points = self.data.get_slice_length() / ENVELOPE_RESOLUTION;
+      let slice_start = (slice_end as f32 - slice_points).max(0.);
+
+      let mut slice = vg::Path::new();
+      slice.rect(
+        bounds.x + slice_start * point_width,
+        bounds.y,
+        (slice_end as f32 - slice_start) * point_width,
+        bounds.h,
+      );
+      canvas.fill_path(&slice, &vg::Paint::color(to_vg_color(cx.selection_color())));
+    }
+
+    let center = bounds.y + bounds.h * 0.5;
+    let mut waveform = vg::Path::new();
+    for (index, peak) in self.data.get_envelope().into_iter().enumerate() {
+      let height = peak.min(1.) * bounds.h * 0.5;
+      waveform.rect(
+        bounds.x + index as f32 * point_width,
+        center - height,
+        point_width.max(1.),
+        height * 2.,
+      );
+    }
+    canvas.fill_path(&waveform, &vg::Paint::color(to_vg_color(cx.font_color())));
+  }
+}
+
+fn to_vg_color(color: Color) -> vg::Color {
+  vg::Color::rgba(color.r(), color.g(), color.b(), color.a())
+}
@@ -0,0 +1,56 @@
+use crate::visualizer::{VisualizerData, HISTORY_LENGTH};
+use nih_plug_vizia::vizia::{
+  binding::{Lens, LensExt},
+  context::Context,
+  layout::Units::{Pixels, Stretch},
+  modifiers::{LayoutModifiers, StyleModifiers},
+  view::Handle,
+  views::{HStack, Label},
+};
+use std::sync::Arc;
+use stutter::note_values::get_note_value_name;
+
+/// A lane with the last triggers from old to new, labelled with their note value and the amount
+/// of slices until the next auto trigger. Triggers that the chance rejected are dimmed.
+pub struct TriggerHistory;
+
+impl TriggerHistory {
+  pub fn new<L>(cx: &mut Context, lens: L) -> Handle<HStack>
+  where
+    L: 'static + Lens<Target = Arc<VisualizerData>> + Copy + Send + Sync,
+  {
+    HStack::new(cx, |cx| {
+      (0..HISTORY_LENGTH).for_each(|index| {
+        let trigger = lens.map(move |data| data.get_trigger_history()[index]);
+
+        Label::new(
+          cx,
+          trigger.map(|trigger| match trigger {
+            Some(trigger) if trigger.duration_multiple > 1. => format!(
+              "{} x{}",
+              Self::get_name(trigger.time_fraction),
+              trigger.duration_multiple
+            ),
+            Some(trigger) => Self::get_name(trigger.time_fraction),
+            None => String::new(),
+          }),
+        )
+        .class("trigger")
+        .toggle_class(
+          "active",
+          trigger.map(|trigger| trigger.is_some_and(|trigger| trigger.is_active)),
+        )
+        .toggle_class("empty", trigger.map(|trigger| trigger.is_none()))
+        .width(Stretch(1.0))
+        .height(Pixels(22.0))
+        .child_space(Stretch(1.0));
+      });
+    })
+    .height(Pixels(22.0))
+    .col_between(Pixels(2.0))
+  }
+
+  fn get_name(time_fraction: f32) -> String {
+    get_note_value_name(time_fraction).replace(" tripl.", "T")
+  }
+}
@@ -0,0 +1,62 @@
+use nih_plug_vizia::vizia::prelude::Data;
+use std::{env, path::PathBuf};
+
+/// The look of the editor. Every theme but the dark one is a class on the root of the editor, so
+/// the stylesheets only have to override the colours that differ.
+#[derive(Clone, Copy, PartialEq, Data)]
+pub enum Theme {
+  Dark,
+  Light,
+  HighContrast,
+}
+
+impl Theme {
+  pub const ALL: [Theme; 3] = [Theme::Dark, Theme::Light, Theme::HighContrast];
+
+  pub fn get_name(&self) -> &'static str {
+    match self {
+      Theme::Dark => "Dark",
+      Theme::Light => "Light",
+      Theme::HighContrast => "High contrast",
+    }
+  }
+
+  pub fn get_class(&self) -> &'static str {
+    match self {
+      Theme::Dark => "dark",
+      Theme::Light => "light",
+      Theme::HighContrast => "high-contrast",
+    }
+  }
+
+  /// Parses the persisted class name. Anything unknown falls back to the dark theme.
+  pub fn from_class(class: &str) -> Self {
+    Self::ALL
+      .into_iter()
+      .find(|theme| theme.get_class() == class)
+      .unwrap_or(Theme::Dark)
+  }
+
+  pub fn next(&self) -> Self {
+    let index = Self::ALL
+      .iter()
+      .position(|theme| theme == self)
+      .unwrap_or(0);
+    Self::ALL[(index + 1) % Self::ALL.len()]
+  }
+}
+
+/// Returns the path of the stylesheet users can write to override any style of the editor. It
+/// lives in the config directory of the platform, for example `~/.config/dm-Stutter/style.css`.
+pub fn get_user_stylesheet_path() -> Option<PathBuf> {
+  let config_dir = if cfg!(target_os = "windows") {
+    PathBuf::from(env::var_os("APPDATA")?)
+  } else if cfg!(target_os = "macos") {
+    PathBuf::from(env::var_os("HOME")?).join("Library/Application Support")
+  } else {
+    env::var_os("XDG_CONFIG_HOME")
+      .map(PathBuf::from)
+      .or_else(|| Some(PathBuf::from(env::var_os("HOME")?).join(".config")))?
+  };
+  Some(config_dir.join("dm-Stutter").join("style.css"))
+}
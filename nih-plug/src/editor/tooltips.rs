@@ -0,0 +1,68 @@
+/// Returns the explanation that shows when hovering a control, by the id of its parameter.
+/// Several parameters share a display name, like the pulse time and the tempo factor.
+pub fn get_tooltip(id: &str) -> Option<&'static str> {
+  let tooltip = match id {
+    "on" => "Switches the stutter on. While off, the input passes through.",
+    "trigger" => "Captures a new slice right away.",
+    "tap" => "Tap twice or more to set the pulse.",
+    "auto" => "Triggers by itself at the end of every duration.",
+    "sync" => "Follows the tempo of the host.",
+    "clock" => "Follows clock pulses on the sidechain input instead of the host tempo.",
+    "ppqn" => "The amount of clock pulses per quarter note.",
+    "free_ratio" => "Sets the synced pulse with a free ratio instead of fixed note values.",
+    "stretch" => "Lets playing slices follow tempo changes, which briefly bends their pitch.",
+    "freeze" => "Keeps repeating the current slice by ignoring auto and sidechain triggers.",
+    "mix" => {
+      "Dry or wet plays the stutter instead of the input while it's active. Dry and wet plays \
+       it on top of the input. Wet only plays nothing but the stutter."
+    }
+    "pulse" => "The length of a quarter note, which the note values are relative to.",
+    "tempo_factor" => {
+      "Multiplies the synced pulse, for example 0.5 for half time or 1/3 for triplets."
+    }
+    "sync_mode" => {
+      "What the synced pulse follows. A quarter note, the beat of the time signature, or a \
+       quarter of a bar."
+    }
+    "ratio" => "Multiplies the synced pulse by any ratio between 0.25 and 4.",
+    "chance" => {
+      "The chance that an auto trigger is heard. Rejected triggers let the input through. \
+       Manual triggers are always heard."
+    }
+    "duration" => {
+      "The longest time until the next auto trigger, up to eight pulses. Every trigger picks a \
+       random whole number of slices within it."
+    }
+    "forced_trigger" => "Triggers right away with the forced note value.",
+    "forced_note" => "The note value of the forced trigger.",
+    "forced_repeats" => {
+      "How many times the slice of the forced trigger plays. Random follows the duration."
+    }
+    "note_triggers" => "Lets MIDI notes from note 36 up trigger with their own note value.",
+    "note_channel" => {
+      "The MIDI channel that note triggers listen to. Omni listens to every channel."
+    }
+    "pan_pattern" => {
+      "Pans every repeat. Alternate moves between left and right, random picks any position \
+       and sweep moves across in steps."
+    }
+    "pan_amount" => "How far the pan pattern moves to the sides.",
+    "width" => "The stereo width of the stutter. Zero is mono and 200 % is extra wide.",
+    "sidechain" => "Lets transients in the sidechain input trigger the stutter.",
+    "sensitivity" => "How easily a transient in the sidechain is detected.",
+    "hold_off" => "The shortest time between two detected transients.",
+    "quantize" => "Lets every trigger wait up to this long for the next transient in the input.",
+    "quantize_sensitivity" => "How easily a transient in the input is detected for quantize.",
+    "trigger_note" => "The MIDI note that is sent on every trigger.",
+    "repeat_note" => "The MIDI note that is sent on every repeat of a slice.",
+    "note_velocity" => "The velocity of the MIDI notes that are sent.",
+    "triplet_balance" => "Weighs triplets against straight notes.",
+    "odd_balance" => "Weighs odd note values like 3/16 against straight notes.",
+    "tilt" => "Favours short notes when turned down and long notes when turned up.",
+    id if id.ends_with("_notes") => {
+      "How likely a trigger picks this note value, relative to the other note values."
+    }
+    _ => return None,
+  };
+  Some(tooltip)
+}
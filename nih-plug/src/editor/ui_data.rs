@@ -1,20 +1,142 @@
-use crate::stutter_parameters::StutterParameters;
-use nih_plug::prelude::{GuiContext, ParamPtr};
-use nih_plug_vizia::vizia::prelude::*;
-use std::sync::Arc;
+use super::{page_tabs::Page, theme::Theme};
+use crate::{
+  forced_fraction::ForcedFraction, stutter_parameters::StutterParameters,
+  visualizer::VisualizerData,
+};
+use nih_plug::prelude::{GuiContext, ParamPtr, Params};
+use nih_plug_vizia::{vizia::prelude::*, widgets::GuiContextEvent};
+use std::{collections::HashSet, sync::Arc};
+use stutter::{
+  note_values::NOTE_VALUES,
+  presets::{FACTORY_PRESETS, PROBABILITY_IDS},
+  probability_randomizer::{mutate_probability, randomize_probability},
+};
+
+// The number keys force the note value of the next trigger. The top row goes from a half note to
+// a 64th note, followed by the triplets. The values are indices into `NOTE_VALUES`.
+const NUMBER_KEYS: [(Code, usize); 10] = [
+  (Code::Digit1, 0),
+  (Code::Digit2, 5),
+  (Code::Digit3, 8),
+  (Code::Digit4, 10),
+  (Code::Digit5, 12),
+  (Code::Digit6, 14),
+  (Code::Digit7, 3),
+  (Code::Digit8, 7),
+  (Code::Digit9, 9),
+  (Code::Digit0, 11),
+];
 
 pub enum ParamChangeEvent {
   SetParam(ParamPtr, f32),
+  LoadPreset(usize),
+  RandomizeProbability,
+  MutateProbability,
+  UndoProbability,
+  SetProbabilityConstraint(i32),
+  SetMutateAmount(f32),
+  SetScaleFactor(f64),
+  SetPage(Page),
+  LearnMidi(ParamPtr),
+  SetTheme(Theme),
+  StartValueEntry(ParamPtr),
+  EndValueEntry,
 }
 
 #[derive(Lens)]
 pub struct UiData {
   pub params: Arc<StutterParameters>,
   pub gui_context: Arc<dyn GuiContext>,
+  pub visualizer_data: Arc<VisualizerData>,
+  pub forced_fraction: Arc<ForcedFraction>,
+  pub preset_index: Option<usize>,
+  pub probability_constraint: i32,
+  pub mutate_amount: f32,
+  pub probability_undo: Option<[f32; 15]>,
+  pub scale_factor: f64,
+  pub editing_param: Option<ParamPtr>,
+  pub keys_down: HashSet<Code>,
+  pub theme: Theme,
+  pub page: Page,
+}
+
+impl UiData {
+  fn load_preset(&mut self, index: usize) {
+    self.set_param_values(FACTORY_PRESETS[index].get_values());
+    self.preset_index = Some(index);
+  }
+
+  // The current note probabilities are kept, so the last randomize or mutate can be undone.
+  fn set_probability(&mut self, probability: [f32; 15]) {
+    self.probability_undo = Some(self.get_probability());
+    self.set_param_values(PROBABILITY_IDS.into_iter().zip(probability));
+  }
+
+  fn undo_probability(&mut self) {
+    if let Some(probability) = self.probability_undo.take() {
+      self.set_param_values(PROBABILITY_IDS.into_iter().zip(probability));
+    }
+  }
+
+  fn get_probability(&self) -> [f32; 15] {
+    let param_map = self.params.param_map();
+
+    PROBABILITY_IDS.map(|id| {
+      param_map
+        .iter()
+        .find(|(param_id, _, _)| param_id == id)
+        .map_or(0., |(_, param_ptr, _)| unsafe {
+          param_ptr.modulated_plain_value()
+        })
+    })
+  }
+
+  // Space triggers, the number keys force the note value of the next trigger and F toggles
+  // freeze. With shift, the number keys trigger right away. Holding a key down doesn't repeat it,
+  // because the key repeat of the OS only sends more key down events.
+  fn handle_key_down(&mut self, code: Code, is_shift_down: bool) {
+    if !self.keys_down.insert(code) {
+      return;
+    }
+
+    match code {
+      Code::Space => self.toggle_param("trigger", self.params.trigger.value()),
+      Code::KeyF => self.toggle_param("freeze", self.params.freeze.value()),
+      _ => {
+        if let Some((_, index)) = NUMBER_KEYS.iter().find(|(key, _)| *key == code) {
+          self
+            .forced_fraction
+            .request(NOTE_VALUES[*index].1, is_shift_down);
+        }
+      }
+    }
+  }
+
+  fn toggle_param(&self, id: &'static str, value: bool) {
+    self.set_param_values([(id, if value { 0. } else { 1. })]);
+  }
+
+  // Every value is set as a separate gesture, so hosts can record the change as automation.
+  fn set_param_values(&self, values: impl IntoIterator<Item = (&'static str, f32)>) {
+    let param_map = self.params.param_map();
+
+    for (id, value) in values {
+      if let Some((_, param_ptr, _)) = param_map.iter().find(|(param_id, _, _)| param_id == id) {
+        unsafe {
+          let normalized_value = param_ptr.preview_normalized(value);
+          self.gui_context.raw_begin_set_parameter(*param_ptr);
+          self
+            .gui_context
+            .raw_set_parameter_normalized(*param_ptr, normalized_value);
+          self.gui_context.raw_end_set_parameter(*param_ptr);
+        }
+      }
+    }
+  }
 }
 
 impl Model for UiData {
-  fn event(&mut self, _: &mut EventContext, event: &mut Event) {
+  fn event(&mut self, cx: &mut EventContext, event: &mut Event) {
     event.map(|app_event, _| match app_event {
       ParamChangeEvent::SetParam(param_ptr, value) => {
         unsafe {
@@ -23,6 +145,49 @@ impl Model for UiData {
             .raw_set_parameter_normalized(*param_ptr, *value)
         };
       }
+      ParamChangeEvent::LoadPreset(index) => self.load_preset(*index),
+      ParamChangeEvent::RandomizeProbability => {
+        self.set_probability(randomize_probability(self.probability_constraint))
+      }
+      ParamChangeEvent::MutateProbability => self.set_probability(mutate_probability(
+        self.get_probability(),
+        self.mutate_amount,
+        self.probability_constraint,
+      )),
+      ParamChangeEvent::UndoProbability => self.undo_probability(),
+      ParamChangeEvent::SetProbabilityConstraint(constraint) => {
+        self.probability_constraint = *constraint
+      }
+      ParamChangeEvent::SetMutateAmount(amount) => self.mutate_amount = *amount,
+      ParamChangeEvent::LearnMidi(param_ptr) => self.params.midi_learn.learn(*param_ptr),
+      ParamChangeEvent::StartValueEntry(param_ptr) => self.editing_param = Some(*param_ptr),
+      ParamChangeEvent::EndValueEntry => self.editing_param = None,
+      ParamChangeEvent::SetTheme(theme) => {
+        self.theme = *theme;
+        *self.params.theme.write().unwrap() = theme.get_class().to_string();
+      }
+      // The window model of nih_plug_vizia resizes the window and stores the scale factor in the
+      // editor state, so the host restores it with the project.
+      ParamChangeEvent::SetScaleFactor(scale_factor) => {
+        self.scale_factor = *scale_factor;
+        cx.set_user_scale_factor(*scale_factor);
+        cx.emit(GuiContextEvent::Resize);
+      }
+      ParamChangeEvent::SetPage(page) => self.page = *page,
+    });
+
+    event.map(|window_event, _| match window_event {
+      // Keys go to the value entry while a value is typed
+      WindowEvent::KeyDown(code, _) if self.editing_param.is_none() => {
+        self.handle_key_down(*code, cx.modifiers().contains(Modifiers::SHIFT))
+      }
+      WindowEvent::KeyUp(code, _) => {
+        self.keys_down.remove(code);
+      }
+      // The resize handle scales the editor by itself, so the scale selector picks it up when the
+      // drag ends
+      WindowEvent::MouseUp(_) => self.scale_factor = cx.user_scale_factor(),
+      _ => {}
     });
   }
 }
@@ -0,0 +1,45 @@
+use nih_plug::prelude::AtomicF32;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// A trigger with an explicit time fraction, from the editor, a MIDI note or automation.
+#[derive(Clone, Copy)]
+pub enum ForcedTrigger {
+  /// The next manual trigger uses this time fraction.
+  Next(f32),
+  /// Triggers right away with this time fraction and optionally a duration multiple.
+  Now(f32, Option<f32>),
+}
+
+/// A time fraction the editor requests, either for the next manual trigger or to trigger right
+/// away. The audio thread takes it once per block and passes it on to the stutter. Zero means
+/// nothing is requested.
+pub struct ForcedFraction {
+  time_fraction: AtomicF32,
+  is_triggered: AtomicBool,
+}
+
+impl ForcedFraction {
+  pub fn new() -> Self {
+    Self {
+      time_fraction: AtomicF32::new(0.),
+      is_triggered: AtomicBool::new(false),
+    }
+  }
+
+  pub fn request(&self, time_fraction: f32, is_triggered: bool) {
+    self.is_triggered.store(is_triggered, Ordering::Relaxed);
+    self.time_fraction.store(time_fraction, Ordering::Relaxed);
+  }
+
+  /// Returns the requested trigger once.
+  pub fn take(&self) -> Option<ForcedTrigger> {
+    let time_fraction = self.time_fraction.swap(0., Ordering::Relaxed);
+    if time_fraction <= 0. {
+      None
+    } else if self.is_triggered.load(Ordering::Relaxed) {
+      Some(ForcedTrigger::Now(time_fraction, None))
+    } else {
+      Some(ForcedTrigger::Next(time_fraction))
+    }
+  }
+}
@@ -1,38 +1,305 @@
 use nih_plug::prelude::*;
-use stutter::Stutter;
+use stutter::{
+  note_values::{get_midi_note_value, is_note_channel, NOTE_VALUES},
+  sync::{get_pulse_length, get_synced_pulse_time},
+  tempo_factor::{clamp_ratio, map_tempo_factor},
+  ClockFollower, Settings, Stutter, TapTempo,
+};
+mod forced_fraction;
+mod midi_learn;
+mod midi_output;
+mod stutter_engine;
 mod stutter_parameters;
-use std::sync::Arc;
+mod visualizer;
+use forced_fraction::{ForcedFraction, ForcedTrigger};
+use midi_learn::MidiControl;
+use midi_output::MidiOutput;
+use std::{array, ops::Range, sync::Arc};
+use stutter_engine::StutterEngine;
 use stutter_parameters::StutterParameters;
+use visualizer::{Visualizer, VisualizerData};
 mod editor;
 
+// Clock pulses on the sidechain input count from half of full scale.
+const CLOCK_GATE_THRESHOLD: f32 = 0.5;
+
+/// Work that is handed off from the audio thread.
+pub enum Task {
+  // Sets the parameters of learned CCs through the host
+  ApplyMidiControl,
+}
+
+/// The part of the buffer between two events, with the settings that apply to it.
+struct SubBlock {
+  samples: Range<usize>,
+  pulse: f32,
+  forced_trigger: Option<ForcedTrigger>,
+}
+
 struct DmStutter {
   params: Arc<StutterParameters>,
-  stutter: Stutter,
+  stutter: StutterEngine,
+  input_channels: usize,
+  midi_output: MidiOutput,
+  midi_control: MidiControl,
+  tap_tempo: TapTempo,
+  clock_follower: ClockFollower,
+  prev_pulse: f32,
+  prev_forced_trigger: bool,
+  visualizer: Visualizer,
+  visualizer_data: Arc<VisualizerData>,
+  forced_fraction: Arc<ForcedFraction>,
 }
 
-pub fn map_tempo_factor(value: i32) -> f32 {
-  match value {
-    0 => 0.25,
-    1 => 0.5,
-    2 => 1.,
-    3 => 2.,
-    4 => 4.,
-    _ => panic!("Unsupported value for tempo factor was found."),
+impl DmStutter {
+  fn get_tempo_factor(&self) -> f32 {
+    if self.midi_control.value(&self.params.free_ratio) {
+      clamp_ratio(self.midi_control.value(&self.params.ratio))
+    } else {
+      map_tempo_factor(self.midi_control.value(&self.params.tempo_factor) as f32)
+    }
+  }
+
+  fn get_synced_pulse_time(&self, transport: &Transport) -> f32 {
+    get_synced_pulse_time(
+      transport.tempo.unwrap_or(120.) as f32,
+      transport.time_sig_numerator.unwrap_or(4) as f32,
+      transport.time_sig_denominator.unwrap_or(4) as f32,
+      self.midi_control.value(&self.params.sync_mode) as i32,
+    ) * self.get_tempo_factor()
   }
-}
 
-impl DmStutter {
-  fn get_synced_pulse_time(&self, bpm: f32) -> f32 {
-    60000. / bpm * map_tempo_factor(self.params.tempo_factor.value())
+  // Returns None while the clock is off or until it has been measured.
+  fn get_clock_pulse_time(&self, transport: &Transport) -> Option<f32> {
+    if !self.midi_control.value(&self.params.clock) {
+      return None;
+    }
+    self.clock_follower.get_pulse_time().map(|pulse| {
+      pulse
+        * get_pulse_length(
+          transport.time_sig_numerator.unwrap_or(4) as f32,
+          transport.time_sig_denominator.unwrap_or(4) as f32,
+          self.midi_control.value(&self.params.sync_mode) as i32,
+        )
+        * self.get_tempo_factor()
+    })
+  }
+
+  // nih-plug doesn't pass MIDI clock messages to the plugin, so the clock follows pulses on the
+  // sidechain input instead.
+  fn follow_clock(&mut self, sidechain: Option<&Buffer>, block_length: usize) {
+    if self.midi_control.value(&self.params.clock) {
+      self
+        .clock_follower
+        .set_ppqn(self.midi_control.value(&self.params.ppqn) as f32);
+      if let Some(channels) = sidechain.map(|buffer| buffer.as_slice_immutable()) {
+        for offset in 0..block_length {
+          let is_high = channels
+            .iter()
+            .any(|channel| channel[offset] >= CLOCK_GATE_THRESHOLD);
+          self.clock_follower.process_gate(is_high, offset as u32);
+        }
+      }
+    } else {
+      self.clock_follower.restart();
+    }
+    self.clock_follower.advance(block_length as u32);
+  }
+
+  // The tapped tempo overrides the pulse knob until the knob is moved again.
+  fn get_tapped_pulse_time(&mut self) -> f32 {
+    let pulse = self.midi_control.value(&self.params.pulse);
+    let is_tapped = self
+      .tap_tempo
+      .process(self.midi_control.value(&self.params.tap), false);
+    if pulse != self.prev_pulse && !is_tapped {
+      self.tap_tempo.reset();
+    }
+    self.prev_pulse = pulse;
+
+    self
+      .tap_tempo
+      .get_pulse_time()
+      .unwrap_or(pulse)
+      .clamp(10., 3000.)
+  }
+
+  // The forced trigger parameter triggers on every change, like the trigger parameter, so it can
+  // be automated.
+  fn get_automated_forced_trigger(&mut self) -> Option<ForcedTrigger> {
+    let forced_trigger = self.midi_control.value(&self.params.forced_trigger);
+    if forced_trigger == self.prev_forced_trigger {
+      return None;
+    }
+    self.prev_forced_trigger = forced_trigger;
+
+    let forced_note = self.midi_control.value(&self.params.forced_note) as usize;
+    let forced_repeats = self.midi_control.value(&self.params.forced_repeats);
+    Some(ForcedTrigger::Now(
+      NOTE_VALUES[forced_note.min(NOTE_VALUES.len() - 1)].1,
+      (forced_repeats > 0).then_some(forced_repeats as f32),
+    ))
+  }
+
+  fn process_engine<const N: usize>(
+    stutter: &mut Stutter<N>,
+    params: &StutterParameters,
+    buffer: &mut Buffer,
+    sidechain: Option<&Buffer>,
+    input_channels: usize,
+    sub_block: SubBlock,
+    midi_output: &mut MidiOutput,
+    midi_control: &MidiControl,
+    visualizer: &mut Visualizer,
+    context: &mut impl ProcessContext<Self>,
+  ) {
+    let SubBlock {
+      samples,
+      pulse,
+      forced_trigger,
+    } = sub_block;
+    let on = midi_control.value(&params.on);
+    let settings = Settings {
+      on,
+      trigger: midi_control.value(&params.trigger),
+      auto_trigger: midi_control.value(&params.auto),
+      mix: midi_control.value(&params.mix) as i32,
+      pulse,
+      duration: midi_control.value(&params.duration),
+      chance: midi_control.value(&params.chance),
+      is_momentary_trigger: false,
+    };
+    let notes = [
+      midi_control.value(&params.trigger_note) as u8,
+      midi_control.value(&params.repeat_note) as u8,
+    ];
+    let velocity = midi_control.value(&params.note_velocity);
+
+    if !on {
+      midi_output.release(context, samples.start as u32);
+    }
+
+    stutter.set_auto_pan(
+      (midi_control.value(&params.pan_pattern) as i32).into(),
+      midi_control.value(&params.pan_amount),
+      midi_control.value(&params.width),
+    );
+
+    stutter.set_sidechain(
+      midi_control.value(&params.sidechain),
+      midi_control.value(&params.sensitivity),
+      midi_control.value(&params.hold_off),
+    );
+
+    stutter.set_transient_quantize(
+      midi_control.value(&params.quantize),
+      midi_control.value(&params.quantize_sensitivity),
+    );
+
+    stutter.set_time_stretch(midi_control.value(&params.stretch));
+
+    stutter.set_freeze(midi_control.value(&params.freeze));
+
+    match forced_trigger {
+      Some(ForcedTrigger::Next(time_fraction)) => stutter.force_time_fraction(time_fraction),
+      Some(ForcedTrigger::Now(time_fraction, duration_multiple)) => {
+        stutter.trigger_with(time_fraction, duration_multiple)
+      }
+      None => (),
+    }
+
+    stutter.set_probability_macros(
+      midi_control.value(&params.triplet_balance),
+      midi_control.value(&params.odd_balance),
+      midi_control.value(&params.tilt),
+    );
+
+    stutter.set_probability(
+      midi_control.value(&params.half_notes),
+      midi_control.value(&params.seven_sixteenth_notes),
+      midi_control.value(&params.six_sixteenth_notes),
+      midi_control.value(&params.half_triplet_notes),
+      midi_control.value(&params.five_sixteenth_notes),
+      midi_control.value(&params.quarter_notes),
+      midi_control.value(&params.three_sixteenth_notes),
+      midi_control.value(&params.quarter_triplet_notes),
+      midi_control.value(&params.eighth_notes),
+      midi_control.value(&params.eighth_triplet_notes),
+      midi_control.value(&params.sixteenth_notes),
+      midi_control.value(&params.sixteenth_triplet_notes),
+      midi_control.value(&params.thirty_second_notes),
+      midi_control.value(&params.thirty_second_triplet_notes),
+      midi_control.value(&params.sixty_fourth_notes),
+    );
+
+    let sidechain_channels = sidechain.map(|buffer| buffer.as_slice_immutable());
+
+    buffer
+      .iter_samples()
+      .enumerate()
+      .skip(samples.start)
+      .take(samples.len())
+      .for_each(|(sample_index, mut channel_samples)| {
+        // A mono input feeding a stereo output is copied to both channels of the engine
+        let input =
+          array::from_fn(|i| *channel_samples.get_mut(i.min(input_channels - 1)).unwrap());
+        let sidechain_input = match sidechain_channels {
+          Some(channels) if !channels.is_empty() => {
+            channels
+              .iter()
+              .map(|channel| channel[sample_index])
+              .sum::<f32>()
+              / channels.len() as f32
+          }
+          _ => 0.,
+        };
+
+        let (output, trigger_output, repeat_trigger_output) =
+          stutter.process(input, sidechain_input, &settings);
+        midi_output.process(
+          context,
+          sample_index as u32,
+          [trigger_output, repeat_trigger_output],
+          notes,
+          velocity,
+        );
+        visualizer.process(
+          input.iter().sum::<f32>() / N as f32,
+          if trigger_output {
+            stutter.get_last_trigger()
+          } else {
+            None
+          },
+        );
+
+        channel_samples
+          .iter_mut()
+          .zip(output)
+          .for_each(|(sample, output)| *sample = output);
+      });
+
+    visualizer.set_slice(stutter, on);
   }
 }
 
 impl Default for DmStutter {
   fn default() -> Self {
     let params = Arc::new(StutterParameters::default());
+    params.midi_learn.set_params(params.param_map());
+    let visualizer_data = Arc::new(VisualizerData::new());
     Self {
       params: params.clone(),
-      stutter: Stutter::new(44100.),
+      stutter: StutterEngine::new(2, 44100.),
+      input_channels: 2,
+      midi_output: MidiOutput::new(),
+      midi_control: MidiControl::new(params.midi_learn.clone(), params.get_momentary_params()),
+      tap_tempo: TapTempo::new(44100.),
+      clock_follower: ClockFollower::new(44100.),
+      prev_pulse: 0.,
+      prev_forced_trigger: false,
+      visualizer: Visualizer::new(visualizer_data.clone(), 44100.),
+      visualizer_data,
+      forced_fraction: Arc::new(ForcedFraction::new()),
     }
   }
 }
@@ -44,92 +311,193 @@ impl Plugin for DmStutter {
   const EMAIL: &'static str = "davemollen@gmail.com";
   const VERSION: &'static str = env!("CARGO_PKG_VERSION");
 
-  const AUDIO_IO_LAYOUTS: &'static [AudioIOLayout] = &[AudioIOLayout {
-    main_input_channels: NonZeroU32::new(2),
-    main_output_channels: NonZeroU32::new(2),
-    ..AudioIOLayout::const_default()
-  }];
-  const MIDI_INPUT: MidiConfig = MidiConfig::None;
+  const AUDIO_IO_LAYOUTS: &'static [AudioIOLayout] = &[
+    AudioIOLayout {
+      main_input_channels: NonZeroU32::new(2),
+      main_output_channels: NonZeroU32::new(2),
+      aux_input_ports: &[new_nonzero_u32(2)],
+      names: PortNames {
+        aux_inputs: &["Sidechain"],
+        ..PortNames::const_default()
+      },
+      ..AudioIOLayout::const_default()
+    },
+    AudioIOLayout {
+      main_input_channels: NonZeroU32::new(1),
+      main_output_channels: NonZeroU32::new(1),
+      aux_input_ports: &[new_nonzero_u32(1)],
+      names: PortNames {
+        aux_inputs: &["Sidechain"],
+        ..PortNames::const_default()
+      },
+      ..AudioIOLayout::const_default()
+    },
+    AudioIOLayout {
+      main_input_channels: NonZeroU32::new(1),
+      main_output_channels: NonZeroU32::new(2),
+      aux_input_ports: &[new_nonzero_u32(1)],
+      names: PortNames {
+        aux_inputs: &["Sidechain"],
+        ..PortNames::const_default()
+      },
+      ..AudioIOLayout::const_default()
+    },
+    AudioIOLayout {
+      main_input_channels: NonZeroU32::new(6),
+      main_output_channels: NonZeroU32::new(6),
+      aux_input_ports: &[new_nonzero_u32(2)],
+      names: PortNames {
+        aux_inputs: &["Sidechain"],
+        ..PortNames::const_default()
+      },
+      ..AudioIOLayout::const_default()
+    },
+  ];
+  const MIDI_INPUT: MidiConfig = MidiConfig::MidiCCs;
+  const MIDI_OUTPUT: MidiConfig = MidiConfig::Basic;
   const SAMPLE_ACCURATE_AUTOMATION: bool = true;
 
-  // More advanced plugins can use this to run expensive background tasks. See the field's
-  // documentation for more information. `()` means that the plugin does not have any background
-  // tasks.
-  type BackgroundTask = ();
+  type BackgroundTask = Task;
   type SysExMessage = ();
 
   fn params(&self) -> Arc<dyn Params> {
     self.params.clone()
   }
 
+  fn task_executor(&mut self) -> TaskExecutor<Self> {
+    let midi_learn = self.params.midi_learn.clone();
+    Box::new(move |task| match task {
+      Task::ApplyMidiControl => midi_learn.apply_to_host(),
+    })
+  }
+
   fn editor(&mut self, _async_executor: AsyncExecutor<Self>) -> Option<Box<dyn Editor>> {
-    editor::create(self.params.clone(), self.params.editor_state.clone())
+    editor::create(
+      self.params.clone(),
+      self.visualizer_data.clone(),
+      self.forced_fraction.clone(),
+      self.params.editor_state.clone(),
+    )
   }
 
   fn initialize(
     &mut self,
-    _audio_io_layout: &AudioIOLayout,
+    audio_io_layout: &AudioIOLayout,
     buffer_config: &BufferConfig,
     _context: &mut impl InitContext<Self>,
   ) -> bool {
-    self.stutter = Stutter::new(buffer_config.sample_rate);
+    let get_channel_count =
+      |channels: Option<NonZeroU32>| channels.map(NonZeroU32::get).unwrap_or(2) as usize;
+    self.input_channels = get_channel_count(audio_io_layout.main_input_channels);
+    self.stutter = StutterEngine::new(
+      get_channel_count(audio_io_layout.main_output_channels),
+      buffer_config.sample_rate,
+    );
+    self.tap_tempo = TapTempo::new(buffer_config.sample_rate);
+    self.clock_follower = ClockFollower::new(buffer_config.sample_rate);
+    self.visualizer = Visualizer::new(self.visualizer_data.clone(), buffer_config.sample_rate);
     true
   }
 
   fn process(
     &mut self,
     buffer: &mut Buffer,
-    _aux: &mut AuxiliaryBuffers,
+    aux: &mut AuxiliaryBuffers,
     context: &mut impl ProcessContext<Self>,
   ) -> ProcessStatus {
-    let on = self.params.on.value();
-    let trigger = self.params.trigger.value();
-    let auto = self.params.auto.value();
-    let mix = self.params.mix.value() as i32;
-    let pulse = if self.params.sync.value() {
-      let bpm = context.transport().tempo.unwrap_or(120.) as f32;
-      self.get_synced_pulse_time(bpm)
-    } else {
-      self.params.pulse.value()
-    };
-    let duration = self.params.duration.value();
-    let chance = self.params.chance.value();
-
-    self.stutter.set_probability(
-      self.params.half_notes.value(),
-      self.params.seven_sixteenth_notes.value(),
-      self.params.six_sixteenth_notes.value(),
-      self.params.half_triplet_notes.value(),
-      self.params.five_sixteenth_notes.value(),
-      self.params.quarter_notes.value(),
-      self.params.three_sixteenth_notes.value(),
-      self.params.quarter_triplet_notes.value(),
-      self.params.eighth_notes.value(),
-      self.params.eighth_triplet_notes.value(),
-      self.params.sixteenth_notes.value(),
-      self.params.sixteenth_triplet_notes.value(),
-      self.params.thirty_second_notes.value(),
-      self.params.thirty_second_triplet_notes.value(),
-      self.params.sixty_fourth_notes.value(),
-    );
+    self.midi_control.update();
+    let input_channels = self.input_channels;
+    let sidechain = aux.inputs.first();
+    let block_length = buffer.samples();
+    self.follow_clock(sidechain, block_length);
 
-    buffer.iter_samples().for_each(|mut channel_samples| {
-      let channel_iterator = &mut channel_samples.iter_mut();
-      let left_channel = channel_iterator.next().unwrap();
-      let right_channel = channel_iterator.next().unwrap();
-
-      (*left_channel, *right_channel, _, _) = self.stutter.process(
-        (*left_channel, *right_channel),
-        on,
-        trigger,
-        auto,
-        mix,
+    // The buffer is split at every event, so CCs and notes apply from the sample they arrive at
+    let mut next_event = context.next_event();
+    let mut block_start = 0;
+    while block_start < block_length {
+      // MIDI notes from note 36 up trigger right away with their own note value, when note
+      // triggers are enabled and the channel matches
+      let mut note_trigger = None;
+      while let Some(event) = next_event.take_if(|event| event.timing() as usize <= block_start) {
+        match event {
+          NoteEvent::MidiCC { cc, value, .. } => self.midi_control.process_cc(cc, value),
+          NoteEvent::NoteOn { note, channel, .. }
+            if self.midi_control.value(&self.params.note_triggers)
+              && is_note_channel(channel, self.midi_control.value(&self.params.note_channel)) =>
+          {
+            if let Some(time_fraction) = get_midi_note_value(note) {
+              note_trigger = Some(ForcedTrigger::Now(time_fraction, None));
+            }
+          }
+          _ => (),
+        }
+        next_event = context.next_event();
+      }
+      let block_end = next_event.as_ref().map_or(block_length, |event| {
+        (event.timing() as usize).min(block_length)
+      });
+
+      let pulse = if let Some(pulse) = self.get_clock_pulse_time(context.transport()) {
+        pulse
+      } else if self.midi_control.value(&self.params.sync) {
+        self.get_synced_pulse_time(context.transport())
+      } else {
+        self.get_tapped_pulse_time()
+      };
+      self.tap_tempo.advance((block_end - block_start) as u32);
+      let sub_block = SubBlock {
+        samples: block_start..block_end,
         pulse,
-        duration,
-        chance,
-        false,
-      );
-    });
+        forced_trigger: self
+          .get_automated_forced_trigger()
+          .or(note_trigger)
+          .or_else(|| self.forced_fraction.take()),
+      };
+
+      match &mut self.stutter {
+        StutterEngine::Mono(stutter) => Self::process_engine(
+          stutter,
+          &self.params,
+          buffer,
+          sidechain,
+          input_channels,
+          sub_block,
+          &mut self.midi_output,
+          &self.midi_control,
+          &mut self.visualizer,
+          context,
+        ),
+        StutterEngine::Stereo(stutter) => Self::process_engine(
+          stutter,
+          &self.params,
+          buffer,
+          sidechain,
+          input_channels,
+          sub_block,
+          &mut self.midi_output,
+          &self.midi_control,
+          &mut self.visualizer,
+          context,
+        ),
+        StutterEngine::Surround(stutter) => Self::process_engine(
+          stutter,
+          &self.params,
+          buffer,
+          sidechain,
+          input_channels,
+          sub_block,
+          &mut self.midi_output,
+          &self.midi_control,
+          &mut self.visualizer,
+          context,
+        ),
+      }
+      block_start = block_end;
+    }
+
+    if self.midi_control.take_host_values() {
+      context.execute_gui(Task::ApplyMidiControl);
+    }
     ProcessStatus::Normal
   }
 
@@ -145,7 +513,9 @@ impl ClapPlugin for DmStutter {
   const CLAP_SUPPORT_URL: Option<&'static str> = None;
   const CLAP_FEATURES: &'static [ClapFeature] = &[
     ClapFeature::AudioEffect,
+    ClapFeature::Mono,
     ClapFeature::Stereo,
+    ClapFeature::Surround,
     ClapFeature::Delay,
   ];
 }
@@ -155,7 +525,9 @@ impl Vst3Plugin for DmStutter {
   const VST3_SUBCATEGORIES: &'static [Vst3SubCategory] = &[
     Vst3SubCategory::Fx,
     Vst3SubCategory::Delay,
+    Vst3SubCategory::Mono,
     Vst3SubCategory::Stereo,
+    Vst3SubCategory::Surround,
   ];
 }
 
@@ -0,0 +1,276 @@
+use nih_plug::{
+  params::persist::PersistentField,
+  prelude::{GuiContext, Param, ParamPtr},
+};
+use std::sync::{
+  atomic::{AtomicU32, AtomicUsize, Ordering},
+  Arc, OnceLock, RwLock,
+};
+
+pub const CC_COUNT: usize = 128;
+const NO_PARAM: usize = usize::MAX;
+// The bits of a NaN, which no normalized value can be
+const NO_VALUE: u32 = u32::MAX;
+
+/// The MIDI CC mappings that are made with MIDI learn. The editor picks the parameter to learn
+/// and the audio thread maps it to the next CC it receives. Every mapping is an atomic, so the
+/// audio thread never waits for the editor. The mappings are saved with the plugin state by
+/// parameter id.
+pub struct MidiLearn {
+  params: OnceLock<Vec<(String, ParamPtr)>>,
+  mappings: [AtomicUsize; CC_COUNT],
+  learn_target: AtomicUsize,
+  host_values: [AtomicU32; CC_COUNT],
+  gui_context: RwLock<Option<Arc<dyn GuiContext>>>,
+}
+
+impl MidiLearn {
+  pub fn new() -> Self {
+    Self {
+      params: OnceLock::new(),
+      mappings: std::array::from_fn(|_| AtomicUsize::new(NO_PARAM)),
+      learn_target: AtomicUsize::new(NO_PARAM),
+      host_values: std::array::from_fn(|_| AtomicU32::new(NO_VALUE)),
+      gui_context: RwLock::new(None),
+    }
+  }
+
+  /// nih-plug only hands out the context that sets parameters through the host to the editor, so
+  /// CCs reach the host once the editor has been opened.
+  pub fn set_gui_context(&self, gui_context: Arc<dyn GuiContext>) {
+    if let Ok(mut current) = self.gui_context.write() {
+      *current = Some(gui_context);
+    }
+  }
+
+  /// Has to be called once the parameters exist, before any mapping can be made or restored.
+  pub fn set_params(&self, param_map: Vec<(String, ParamPtr, String)>) {
+    self
+      .params
+      .set(
+        param_map
+          .into_iter()
+          .map(|(id, param_ptr, _)| (id, param_ptr))
+          .collect(),
+      )
+      .ok();
+  }
+
+  /// Starts learning the parameter. Learning the same parameter again cancels learning and
+  /// removes the mappings of the parameter.
+  pub fn learn(&self, param_ptr: ParamPtr) {
+    let Some(index) = self.get_index(param_ptr) else {
+      return;
+    };
+    if self.learn_target.load(Ordering::Relaxed) == index {
+      self.learn_target.store(NO_PARAM, Ordering::Relaxed);
+      self.mappings.iter().for_each(|mapping| {
+        let _ = mapping.compare_exchange(index, NO_PARAM, Ordering::Relaxed, Ordering::Relaxed);
+      });
+    } else {
+      self.learn_target.store(index, Ordering::Relaxed);
+    }
+  }
+
+  pub fn is_learning(&self, param_ptr: ParamPtr) -> bool {
+    self
+      .get_index(param_ptr)
+      .is_some_and(|index| self.learn_target.load(Ordering::Relaxed) == index)
+  }
+
+  /// Returns the parameter that is mapped to the CC. While learning, the CC is mapped to the
+  /// parameter that is learned first. Called from the audio thread.
+  pub fn process_cc(&self, cc: u8) -> Option<ParamPtr> {
+    let mapping = self.mappings.get(cc as usize)?;
+    let learn_target = self.learn_target.swap(NO_PARAM, Ordering::Relaxed);
+    if learn_target != NO_PARAM {
+      mapping.store(learn_target, Ordering::Relaxed);
+    }
+
+    self
+      .params
+      .get()?
+      .get(mapping.load(Ordering::Relaxed))
+      .map(|(_, param_ptr)| *param_ptr)
+  }
+
+  /// Queues the normalized value of a CC for the host. Called from the audio thread.
+  pub fn send_to_host(&self, cc: u8, value: f32) {
+    if let Some(host_value) = self.host_values.get(cc as usize) {
+      host_value.store(value.to_bits(), Ordering::Relaxed);
+    }
+  }
+
+  /// Sets the parameters of the queued CC values through the host, so the knobs follow the
+  /// controller and the host can record it as automation. Runs on the GUI thread.
+  pub fn apply_to_host(&self) {
+    let Ok(gui_context) = self.gui_context.read() else {
+      return;
+    };
+    let (Some(gui_context), Some(params)) = (gui_context.as_ref(), self.params.get()) else {
+      return;
+    };
+
+    for (mapping, host_value) in self.mappings.iter().zip(&self.host_values) {
+      let value = host_value.swap(NO_VALUE, Ordering::Relaxed);
+      if value == NO_VALUE {
+        continue;
+      }
+      if let Some((_, param_ptr)) = params.get(mapping.load(Ordering::Relaxed)) {
+        unsafe {
+          gui_context.raw_begin_set_parameter(*param_ptr);
+          gui_context.raw_set_parameter_normalized(*param_ptr, f32::from_bits(value));
+          gui_context.raw_end_set_parameter(*param_ptr);
+        }
+      }
+    }
+  }
+
+  fn get_index(&self, param_ptr: ParamPtr) -> Option<usize> {
+    self
+      .params
+      .get()?
+      .iter()
+      .position(|(_, ptr)| *ptr == param_ptr)
+  }
+}
+
+impl<'a> PersistentField<'a, Vec<(u8, String)>> for MidiLearn {
+  fn set(&self, new_value: Vec<(u8, String)>) {
+    self
+      .mappings
+      .iter()
+      .for_each(|mapping| mapping.store(NO_PARAM, Ordering::Relaxed));
+
+    let Some(params) = self.params.get() else {
+      return;
+    };
+    for (cc, id) in new_value {
+      if let (Some(mapping), Some(index)) = (
+        self.mappings.get(cc as usize),
+        params.iter().position(|(param_id, _)| *param_id == id),
+      ) {
+        mapping.store(index, Ordering::Relaxed);
+      }
+    }
+  }
+
+  fn map<F, R>(&self, f: F) -> R
+  where
+    F: Fn(&Vec<(u8, String)>) -> R,
+  {
+    let params = self.params.get();
+    let mappings = self
+      .mappings
+      .iter()
+      .enumerate()
+      .filter_map(|(cc, mapping)| {
+        params?
+          .get(mapping.load(Ordering::Relaxed))
+          .map(|(id, _)| (cc as u8, id.clone()))
+      })
+      .collect();
+    f(&mappings)
+  }
+}
+
+struct Override {
+  param_ptr: ParamPtr,
+  value: f32,
+  host_value: f32,
+}
+
+/// Applies the MIDI CC mappings on the audio thread. A CC overrides the value of its parameter
+/// right away, and is sent on to the host. The override lasts until the host changes the
+/// parameter, which is usually when the CC has arrived there.
+pub struct MidiControl {
+  midi_learn: Arc<MidiLearn>,
+  momentary_params: Vec<ParamPtr>,
+  is_pressed: [bool; CC_COUNT],
+  overrides: Vec<Override>,
+  has_host_values: bool,
+}
+
+impl MidiControl {
+  /// The momentary parameters trigger on every change, like the trigger parameter. A CC that is
+  /// mapped to one of them acts like a button instead, which only triggers when it's pressed.
+  pub fn new(midi_learn: Arc<MidiLearn>, momentary_params: Vec<ParamPtr>) -> Self {
+    Self {
+      midi_learn,
+      momentary_params,
+      is_pressed: [false; CC_COUNT],
+      // Every CC can override one parameter, so this never allocates on the audio thread
+      overrides: Vec::with_capacity(CC_COUNT),
+      has_host_values: false,
+    }
+  }
+
+  pub fn process_cc(&mut self, cc: u8, value: f32) {
+    let Some(param_ptr) = self.midi_learn.process_cc(cc) else {
+      return;
+    };
+    let host_value = unsafe { param_ptr.unmodulated_normalized_value() };
+    let value = if self.momentary_params.contains(&param_ptr) {
+      // A footswitch sends a CC on press and on release, so only the press flips the value
+      let is_pressed = value >= 0.5;
+      let was_pressed = std::mem::replace(&mut self.is_pressed[cc as usize], is_pressed);
+      if !is_pressed || was_pressed {
+        return;
+      }
+      let current_value = self
+        .overrides
+        .iter()
+        .find(|param_override| param_override.param_ptr == param_ptr)
+        .map_or(host_value, |param_override| param_override.value);
+      if current_value >= 0.5 {
+        0.
+      } else {
+        1.
+      }
+    } else {
+      value
+    };
+
+    self.midi_learn.send_to_host(cc, value);
+    self.has_host_values = true;
+
+    match self
+      .overrides
+      .iter_mut()
+      .find(|param_override| param_override.param_ptr == param_ptr)
+    {
+      Some(param_override) => param_override.value = value,
+      None if self.overrides.len() < CC_COUNT => self.overrides.push(Override {
+        param_ptr,
+        value,
+        host_value,
+      }),
+      None => (),
+    }
+  }
+
+  /// Returns whether CC values were queued for the host since the last call.
+  pub fn take_host_values(&mut self) -> bool {
+    std::mem::take(&mut self.has_host_values)
+  }
+
+  /// Removes the overrides of parameters the host has changed. Called once per block.
+  pub fn update(&mut self) {
+    self.overrides.retain(|param_override| {
+      let host_value = unsafe { param_override.param_ptr.unmodulated_normalized_value() };
+      host_value == param_override.host_value
+    });
+  }
+
+  pub fn value<P: Param>(&self, param: &P) -> P::Plain {
+    let param_ptr = param.as_ptr();
+    self
+      .overrides
+      .iter()
+      .find(|param_override| param_override.param_ptr == param_ptr)
+      .map_or_else(
+        || param.modulated_plain_value(),
+        |param_override| param.preview_plain(param_override.value),
+      )
+  }
+}
@@ -0,0 +1,62 @@
+use crate::DmStutter;
+use nih_plug::prelude::{NoteEvent, ProcessContext};
+
+/// Sends the trigger and repeat trigger outputs of the stutter as MIDI notes. Each note is held
+/// until the next note on the same output, so the note length follows the stutter pattern.
+pub struct MidiOutput {
+  active_notes: [Option<u8>; 2],
+}
+
+impl MidiOutput {
+  pub fn new() -> Self {
+    Self {
+      active_notes: [None; 2],
+    }
+  }
+
+  pub fn process(
+    &mut self,
+    context: &mut impl ProcessContext<DmStutter>,
+    timing: u32,
+    triggers: [bool; 2],
+    notes: [u8; 2],
+    velocity: f32,
+  ) {
+    for ((active_note, trigger), note) in self.active_notes.iter_mut().zip(triggers).zip(notes) {
+      if !trigger {
+        continue;
+      }
+
+      if let Some(active_note) = active_note.take() {
+        context.send_event(NoteEvent::NoteOff {
+          timing,
+          voice_id: None,
+          channel: 0,
+          note: active_note,
+          velocity: 0.,
+        });
+      }
+
+      context.send_event(NoteEvent::NoteOn {
+        timing,
+        voice_id: None,
+        channel: 0,
+        note,
+        velocity,
+      });
+      *active_note = Some(note);
+    }
+  }
+
+  pub fn release(&mut self, context: &mut impl ProcessContext<DmStutter>, timing: u32) {
+    for note in self.active_notes.iter_mut().filter_map(Option::take) {
+      context.send_event(NoteEvent::NoteOff {
+        timing,
+        voice_id: None,
+        channel: 0,
+        note,
+        velocity: 0.,
+      });
+    }
+  }
+}
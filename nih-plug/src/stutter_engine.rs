@@ -0,0 +1,17 @@
+use stutter::Stutter;
+
+pub enum StutterEngine {
+  Mono(Stutter<1>),
+  Stereo(Stutter<2>),
+  Surround(Stutter<6>),
+}
+
+impl StutterEngine {
+  pub fn new(channels: usize, sample_rate: f32) -> Self {
+    match channels {
+      1 => Self::Mono(Stutter::new(sample_rate)),
+      6 => Self::Surround(Stutter::new(sample_rate)),
+      _ => Self::Stereo(Stutter::new(sample_rate)),
+    }
+  }
+}
@@ -1,13 +1,19 @@
-use crate::editor;
+use crate::{editor, midi_learn::MidiLearn};
 use nih_plug::{
-  formatters::{s2v_f32_percentage, v2s_f32_percentage},
-  params::{EnumParam, IntParam},
-  prelude::{BoolParam, Enum, FloatParam, FloatRange, IntRange, Params},
+  formatters::{
+    s2v_f32_percentage, s2v_i32_note_formatter, v2s_f32_percentage, v2s_i32_note_formatter,
+  },
+  params::{EnumParam, IntParam, Param},
+  prelude::{BoolParam, Enum, FloatParam, FloatRange, IntRange, ParamPtr, Params},
 };
 use nih_plug_vizia::ViziaState;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
+use stutter::tempo_factor::{DEFAULT_TEMPO_FACTOR, MAX_RATIO, MIN_RATIO, TEMPO_FACTORS};
 mod custom_formatters;
-use custom_formatters::{s2v_f32_tempo_factor, v2s_f32_digits, v2s_f32_tempo_factor};
+use custom_formatters::{
+  s2v_f32_tempo_factor, s2v_i32_note_channel, s2v_i32_note_value, v2s_f32_digits,
+  v2s_f32_tempo_factor, v2s_i32_note_channel, v2s_i32_note_value, v2s_i32_repeats,
+};
 
 #[derive(Enum, PartialEq)]
 pub enum Mix {
@@ -19,17 +25,43 @@ pub enum Mix {
   WetOnly,
 }
 
+#[derive(Enum, PartialEq)]
+pub enum PanPattern {
+  Off,
+  Alternate,
+  Random,
+  Sweep,
+}
+
+#[derive(Enum, PartialEq)]
+pub enum SyncMode {
+  #[name = "Quarter note"]
+  QuarterNote,
+  Beat,
+  Bar,
+}
+
 #[derive(Params)]
 pub struct StutterParameters {
   #[persist = "editor-state"]
   pub editor_state: Arc<ViziaState>,
 
+  #[persist = "midi-mappings"]
+  pub midi_learn: Arc<MidiLearn>,
+
+  // The class name of the editor theme
+  #[persist = "theme"]
+  pub theme: RwLock<String>,
+
   #[id = "on"]
   pub on: BoolParam,
 
   #[id = "trigger"]
   pub trigger: BoolParam,
 
+  #[id = "tap"]
+  pub tap: BoolParam,
+
   #[id = "auto"]
   pub auto: BoolParam,
 
@@ -45,12 +77,90 @@ pub struct StutterParameters {
   #[id = "tempo_factor"]
   pub tempo_factor: IntParam,
 
+  #[id = "sync_mode"]
+  pub sync_mode: EnumParam<SyncMode>,
+
+  #[id = "free_ratio"]
+  pub free_ratio: BoolParam,
+
+  #[id = "ratio"]
+  pub ratio: FloatParam,
+
+  #[id = "clock"]
+  pub clock: BoolParam,
+
+  #[id = "ppqn"]
+  pub ppqn: IntParam,
+
+  #[id = "stretch"]
+  pub stretch: BoolParam,
+
+  #[id = "freeze"]
+  pub freeze: BoolParam,
+
+  #[id = "forced_trigger"]
+  pub forced_trigger: BoolParam,
+
+  #[id = "forced_note"]
+  pub forced_note: IntParam,
+
+  #[id = "forced_repeats"]
+  pub forced_repeats: IntParam,
+
+  #[id = "note_triggers"]
+  pub note_triggers: BoolParam,
+
+  #[id = "note_channel"]
+  pub note_channel: IntParam,
+
   #[id = "chance"]
   pub chance: FloatParam,
 
   #[id = "duration"]
   pub duration: FloatParam,
 
+  #[id = "pan_pattern"]
+  pub pan_pattern: EnumParam<PanPattern>,
+
+  #[id = "pan_amount"]
+  pub pan_amount: FloatParam,
+
+  #[id = "width"]
+  pub width: FloatParam,
+
+  #[id = "sidechain"]
+  pub sidechain: BoolParam,
+
+  #[id = "sensitivity"]
+  pub sensitivity: FloatParam,
+
+  #[id = "hold_off"]
+  pub hold_off: FloatParam,
+
+  #[id = "quantize"]
+  pub quantize: FloatParam,
+
+  #[id = "quantize_sensitivity"]
+  pub quantize_sensitivity: FloatParam,
+
+  #[id = "trigger_note"]
+  pub trigger_note: IntParam,
+
+  #[id = "repeat_note"]
+  pub repeat_note: IntParam,
+
+  #[id = "note_velocity"]
+  pub note_velocity: FloatParam,
+
+  #[id = "triplet_balance"]
+  pub triplet_balance: FloatParam,
+
+  #[id = "odd_balance"]
+  pub odd_balance: FloatParam,
+
+  #[id = "tilt"]
+  pub tilt: FloatParam,
+
   #[id = "half_notes"]
   pub half_notes: FloatParam,
 
@@ -97,14 +207,51 @@ pub struct StutterParameters {
   pub sixty_fourth_notes: FloatParam,
 }
 
+impl StutterParameters {
+  /// Returns the note probabilities, ordered from long to short like `Stutter::set_probability`.
+  pub fn get_probability_params(&self) -> [&FloatParam; 15] {
+    [
+      &self.half_notes,
+      &self.seven_sixteenth_notes,
+      &self.six_sixteenth_notes,
+      &self.half_triplet_notes,
+      &self.five_sixteenth_notes,
+      &self.quarter_notes,
+      &self.three_sixteenth_notes,
+      &self.quarter_triplet_notes,
+      &self.eighth_notes,
+      &self.eighth_triplet_notes,
+      &self.sixteenth_notes,
+      &self.sixteenth_triplet_notes,
+      &self.thirty_second_notes,
+      &self.thirty_second_triplet_notes,
+      &self.sixty_fourth_notes,
+    ]
+  }
+
+  /// Returns the parameters that trigger on every change instead of holding a value.
+  pub fn get_momentary_params(&self) -> Vec<ParamPtr> {
+    vec![
+      self.trigger.as_ptr(),
+      self.tap.as_ptr(),
+      self.forced_trigger.as_ptr(),
+    ]
+  }
+}
+
 impl Default for StutterParameters {
   fn default() -> Self {
     Self {
       editor_state: editor::default_state(),
 
+      midi_learn: Arc::new(MidiLearn::new()),
+
+      theme: RwLock::new("dark".to_string()),
+
       on: BoolParam::new("On", false),
 
       trigger: BoolParam::new("Trigger", false),
+      tap: BoolParam::new("Tap", false),
 
       auto: BoolParam::new("Auto", true),
 
@@ -124,10 +271,56 @@ impl Default for StutterParameters {
       .with_unit(" ms")
       .with_value_to_string(v2s_f32_digits(2)),
 
-      tempo_factor: IntParam::new("Pulse", 2, IntRange::Linear { min: 0, max: 4 })
-        .with_unit(" x")
-        .with_value_to_string(v2s_f32_tempo_factor())
-        .with_string_to_value(s2v_f32_tempo_factor()),
+      tempo_factor: IntParam::new(
+        "Pulse",
+        DEFAULT_TEMPO_FACTOR as i32,
+        IntRange::Linear {
+          min: 0,
+          max: TEMPO_FACTORS.len() as i32 - 1,
+        },
+      )
+      .with_unit(" x")
+      .with_value_to_string(v2s_f32_tempo_factor())
+      .with_string_to_value(s2v_f32_tempo_factor()),
+
+      sync_mode: EnumParam::new("Sync to", SyncMode::QuarterNote),
+
+      free_ratio: BoolParam::new("Free ratio", false),
+
+      ratio: FloatParam::new(
+        "Ratio",
+        1.,
+        FloatRange::Skewed {
+          min: MIN_RATIO,
+          max: MAX_RATIO,
+          factor: 0.5,
+        },
+      )
+      .with_unit(" x")
+      .with_value_to_string(v2s_f32_digits(2)),
+
+      clock: BoolParam::new("Clock", false),
+
+      ppqn: IntParam::new("PPQN", 24, IntRange::Linear { min: 1, max: 96 }),
+
+      stretch: BoolParam::new("Stretch", false),
+
+      freeze: BoolParam::new("Freeze", false),
+
+      forced_trigger: BoolParam::new("Forced trigger", false),
+
+      forced_note: IntParam::new("Forced note", 10, IntRange::Linear { min: 0, max: 14 })
+        .with_value_to_string(v2s_i32_note_value())
+        .with_string_to_value(s2v_i32_note_value()),
+
+      forced_repeats: IntParam::new("Repeats", 0, IntRange::Linear { min: 0, max: 16 })
+        .with_value_to_string(v2s_i32_repeats()),
+
+      note_triggers: BoolParam::new("Note triggers", false),
+
+      note_channel: IntParam::new("Note channel", 0, IntRange::Linear { min: 0, max: 16 })
+        .with_value_to_string(v2s_i32_note_channel())
+        .with_string_to_value(s2v_i32_note_channel()),
 
       chance: FloatParam::new("Chance", 0.5, FloatRange::Linear { min: 0., max: 1. })
         .with_unit(" %")
@@ -139,6 +332,86 @@ impl Default for StutterParameters {
         .with_value_to_string(v2s_f32_percentage(2))
         .with_string_to_value(s2v_f32_percentage()),
 
+      pan_pattern: EnumParam::new("Pan pattern", PanPattern::Off),
+
+      pan_amount: FloatParam::new("Pan", 1., FloatRange::Linear { min: 0., max: 1. })
+        .with_unit(" %")
+        .with_value_to_string(v2s_f32_percentage(2))
+        .with_string_to_value(s2v_f32_percentage()),
+
+      width: FloatParam::new("Width", 1., FloatRange::Linear { min: 0., max: 2. })
+        .with_unit(" %")
+        .with_value_to_string(v2s_f32_percentage(2))
+        .with_string_to_value(s2v_f32_percentage()),
+
+      sidechain: BoolParam::new("Sidechain", false),
+
+      sensitivity: FloatParam::new("Sensitivity", 0.5, FloatRange::Linear { min: 0., max: 1. })
+        .with_unit(" %")
+        .with_value_to_string(v2s_f32_percentage(2))
+        .with_string_to_value(s2v_f32_percentage()),
+
+      hold_off: FloatParam::new(
+        "Hold-off",
+        100.,
+        FloatRange::Skewed {
+          min: 10.,
+          max: 1000.,
+          factor: 0.333333,
+        },
+      )
+      .with_unit(" ms")
+      .with_value_to_string(v2s_f32_digits(2)),
+
+      quantize: FloatParam::new(
+        "Quantize",
+        0.,
+        FloatRange::Skewed {
+          min: 0.,
+          max: 500.,
+          factor: 0.5,
+        },
+      )
+      .with_unit(" ms")
+      .with_value_to_string(v2s_f32_digits(2)),
+
+      quantize_sensitivity: FloatParam::new(
+        "Quantize sensitivity",
+        0.5,
+        FloatRange::Linear { min: 0., max: 1. },
+      )
+      .with_unit(" %")
+      .with_value_to_string(v2s_f32_percentage(2))
+      .with_string_to_value(s2v_f32_percentage()),
+
+      trigger_note: IntParam::new("Trigger note", 36, IntRange::Linear { min: 0, max: 127 })
+        .with_value_to_string(v2s_i32_note_formatter())
+        .with_string_to_value(s2v_i32_note_formatter()),
+
+      repeat_note: IntParam::new("Repeat note", 38, IntRange::Linear { min: 0, max: 127 })
+        .with_value_to_string(v2s_i32_note_formatter())
+        .with_string_to_value(s2v_i32_note_formatter()),
+
+      note_velocity: FloatParam::new("Velocity", 1., FloatRange::Linear { min: 0., max: 1. })
+        .with_unit(" %")
+        .with_value_to_string(v2s_f32_percentage(0))
+        .with_string_to_value(s2v_f32_percentage()),
+
+      triplet_balance: FloatParam::new("Triplets", 0., FloatRange::Linear { min: -1., max: 1. })
+        .with_unit(" %")
+        .with_value_to_string(v2s_f32_percentage(0))
+        .with_string_to_value(s2v_f32_percentage()),
+
+      odd_balance: FloatParam::new("Odd", 0., FloatRange::Linear { min: -1., max: 1. })
+        .with_unit(" %")
+        .with_value_to_string(v2s_f32_percentage(0))
+        .with_string_to_value(s2v_f32_percentage()),
+
+      tilt: FloatParam::new("Short/long", 0., FloatRange::Linear { min: -1., max: 1. })
+        .with_unit(" %")
+        .with_value_to_string(v2s_f32_percentage(0))
+        .with_string_to_value(s2v_f32_percentage()),
+
       half_notes: FloatParam::new("1/2", 0., FloatRange::Linear { min: 0., max: 1. })
         .with_unit(" %")
         .with_value_to_string(v2s_f32_percentage(0))
@@ -1,28 +1,67 @@
 use std::sync::Arc;
-
-use crate::map_tempo_factor;
+use stutter::{
+  note_values::NOTE_VALUES,
+  tempo_factor::{get_tempo_factor_name, parse_tempo_factor},
+};
 
 pub fn v2s_f32_digits(digits: usize) -> Arc<dyn Fn(f32) -> String + Send + Sync> {
   Arc::new(move |value| format!("{:.digits$}", value))
 }
 
 pub fn v2s_f32_tempo_factor() -> Arc<dyn Fn(i32) -> String + Send + Sync> {
-  Arc::new(move |value| map_tempo_factor(value).to_string())
+  Arc::new(move |value| get_tempo_factor_name(value as f32).to_string())
 }
 
 pub fn s2v_f32_tempo_factor() -> Arc<dyn Fn(&str) -> Option<i32> + Send + Sync> {
+  Arc::new(|string| parse_tempo_factor(string).map(|index| index as i32))
+}
+
+pub fn v2s_i32_note_value() -> Arc<dyn Fn(i32) -> String + Send + Sync> {
+  Arc::new(move |value| {
+    NOTE_VALUES
+      .get(value as usize)
+      .map_or("", |(name, _)| name)
+      .to_string()
+  })
+}
+
+pub fn s2v_i32_note_value() -> Arc<dyn Fn(&str) -> Option<i32> + Send + Sync> {
   Arc::new(|string| {
-    string
-      .trim_end_matches(&[' ', 'x'])
-      .parse()
-      .ok()
-      .map(|x: f32| match x {
-        0.25 => 0,
-        0.5 => 1,
-        1. => 2,
-        2. => 3,
-        4. => 4,
-        _ => panic!("Unsupported value for tempo factor was found."),
-      })
+    NOTE_VALUES
+      .iter()
+      .position(|(name, _)| name.eq_ignore_ascii_case(string.trim()))
+      .map(|index| index as i32)
+  })
+}
+
+// Zero leaves the amount of repeats to the duration knob.
+pub fn v2s_i32_repeats() -> Arc<dyn Fn(i32) -> String + Send + Sync> {
+  Arc::new(move |value| {
+    if value == 0 {
+      "Random".to_string()
+    } else {
+      format!("x{}", value)
+    }
+  })
+}
+
+pub fn v2s_i32_note_channel() -> Arc<dyn Fn(i32) -> String + Send + Sync> {
+  Arc::new(move |value| {
+    if value == 0 {
+      "Omni".to_string()
+    } else {
+      value.to_string()
+    }
+  })
+}
+
+pub fn s2v_i32_note_channel() -> Arc<dyn Fn(&str) -> Option<i32> + Send + Sync> {
+  Arc::new(move |string| {
+    let string = string.trim();
+    if string.eq_ignore_ascii_case("omni") {
+      Some(0)
+    } else {
+      string.parse().ok()
+    }
   })
 }
@@ -0,0 +1,195 @@
+use nih_plug::prelude::AtomicF32;
+use std::sync::{
+  atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering},
+  Arc,
+};
+use stutter::{Stutter, TriggerEvent};
+
+pub const ENVELOPE_LENGTH: usize = 200;
+// Every point of the envelope covers this many milliseconds, so the editor shows the last two
+// seconds of the input.
+pub const ENVELOPE_RESOLUTION: f32 = 10.;
+pub const HISTORY_LENGTH: usize = 8;
+
+struct AtomicTriggerEvent {
+  time: AtomicU64,
+  time_fraction: AtomicF32,
+  duration_multiple: AtomicF32,
+  is_active: AtomicBool,
+}
+
+impl AtomicTriggerEvent {
+  fn new() -> Self {
+    Self {
+      time: AtomicU64::new(0),
+      time_fraction: AtomicF32::new(1.),
+      duration_multiple: AtomicF32::new(1.),
+      is_active: AtomicBool::new(false),
+    }
+  }
+
+  fn store(&self, event: TriggerEvent) {
+    self.time.store(event.time, Ordering::Relaxed);
+    self
+      .time_fraction
+      .store(event.time_fraction, Ordering::Relaxed);
+    self
+      .duration_multiple
+      .store(event.duration_multiple, Ordering::Relaxed);
+    self.is_active.store(event.is_active, Ordering::Relaxed);
+  }
+
+  fn load(&self) -> TriggerEvent {
+    TriggerEvent {
+      time: self.time.load(Ordering::Relaxed),
+      time_fraction: self.time_fraction.load(Ordering::Relaxed),
+      duration_multiple: self.duration_multiple.load(Ordering::Relaxed),
+      is_active: self.is_active.load(Ordering::Relaxed),
+    }
+  }
+}
+
+/// The state the audio thread shares with the editor. Every value is a separate atomic, so the
+/// audio thread never waits for the editor. The editor might read values from different blocks,
+/// which doesn't matter for drawing.
+pub struct VisualizerData {
+  envelope: [AtomicF32; ENVELOPE_LENGTH],
+  position: AtomicUsize,
+  trigger_position: AtomicUsize,
+  slice_length: AtomicF32,
+  time_fraction: AtomicF32,
+  repeat_count: AtomicU32,
+  is_active: AtomicBool,
+  history: [AtomicTriggerEvent; HISTORY_LENGTH],
+  trigger_count: AtomicUsize,
+}
+
+impl VisualizerData {
+  pub fn new() -> Self {
+    Self {
+      envelope: std::array::from_fn(|_| AtomicF32::new(0.)),
+      position: AtomicUsize::new(0),
+      trigger_position: AtomicUsize::new(0),
+      slice_length: AtomicF32::new(0.),
+      time_fraction: AtomicF32::new(1.),
+      repeat_count: AtomicU32::new(0),
+      is_active: AtomicBool::new(false),
+      history: std::array::from_fn(|_| AtomicTriggerEvent::new()),
+      trigger_count: AtomicUsize::new(0),
+    }
+  }
+
+  /// Returns the envelope from the oldest to the newest point.
+  pub fn get_envelope(&self) -> [f32; ENVELOPE_LENGTH] {
+    let position = self.get_position();
+    std::array::from_fn(|index| {
+      self.envelope[(position + index) % ENVELOPE_LENGTH].load(Ordering::Relaxed)
+    })
+  }
+
+  /// Returns the amount of envelope points that have been written so far.
+  pub fn get_position(&self) -> usize {
+    self.position.load(Ordering::Relaxed)
+  }
+
+  /// Returns how many envelope points ago the last trigger happened.
+  pub fn get_points_since_trigger(&self) -> usize {
+    self
+      .get_position()
+      .saturating_sub(self.trigger_position.load(Ordering::Relaxed))
+  }
+
+  pub fn get_slice_length(&self) -> f32 {
+    self.slice_length.load(Ordering::Relaxed)
+  }
+
+  pub fn get_time_fraction(&self) -> f32 {
+    self.time_fraction.load(Ordering::Relaxed)
+  }
+
+  pub fn get_repeat_count(&self) -> u32 {
+    self.repeat_count.load(Ordering::Relaxed)
+  }
+
+  pub fn is_active(&self) -> bool {
+    self.is_active.load(Ordering::Relaxed)
+  }
+
+  /// Returns the last triggers from the oldest to the newest. Slots without a trigger yet are
+  /// None.
+  pub fn get_trigger_history(&self) -> [Option<TriggerEvent>; HISTORY_LENGTH] {
+    let trigger_count = self.trigger_count.load(Ordering::Acquire);
+    // The oldest trigger sits in the slot that gets overwritten next
+    std::array::from_fn(|index| {
+      let slot = trigger_count + index;
+      if slot < HISTORY_LENGTH {
+        None
+      } else {
+        Some(self.history[slot % HISTORY_LENGTH].load())
+      }
+    })
+  }
+}
+
+/// Writes the input envelope and the state of the stutter to the `VisualizerData` from the audio
+/// thread.
+pub struct Visualizer {
+  data: Arc<VisualizerData>,
+  peak: f32,
+  samples: usize,
+  samples_per_point: usize,
+}
+
+impl Visualizer {
+  pub fn new(data: Arc<VisualizerData>, sample_rate: f32) -> Self {
+    Self {
+      data,
+      peak: 0.,
+      samples: 0,
+      samples_per_point: (sample_rate * ENVELOPE_RESOLUTION / 1000.) as usize,
+    }
+  }
+
+  pub fn process(&mut self, input: f32, trigger: Option<TriggerEvent>) {
+    let position = self.data.get_position();
+    if let Some(trigger) = trigger {
+      self
+        .data
+        .trigger_position
+        .store(position, Ordering::Relaxed);
+
+      let trigger_count = self.data.trigger_count.load(Ordering::Relaxed);
+      self.data.history[trigger_count % HISTORY_LENGTH].store(trigger);
+      self
+        .data
+        .trigger_count
+        .store(trigger_count + 1, Ordering::Release);
+    }
+
+    self.peak = self.peak.max(input.abs());
+    self.samples += 1;
+    if self.samples >= self.samples_per_point {
+      self.data.envelope[position % ENVELOPE_LENGTH].store(self.peak, Ordering::Relaxed);
+      self.data.position.store(position + 1, Ordering::Relaxed);
+      self.peak = 0.;
+      self.samples = 0;
+    }
+  }
+
+  /// Publishes the current slice once per block.
+  pub fn set_slice<const N: usize>(&self, stutter: &Stutter<N>, on: bool) {
+    let data = &self.data;
+    data
+      .slice_length
+      .store(stutter.get_slice_length(), Ordering::Relaxed);
+    data
+      .time_fraction
+      .store(stutter.get_time_fraction(), Ordering::Relaxed);
+    data
+      .repeat_count
+      .store(stutter.get_repeat_count(), Ordering::Relaxed);
+    data
+      .is_active
+      .store(on && stutter.is_active(), Ordering::Relaxed);
+  }
+}
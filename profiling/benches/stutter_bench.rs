@@ -1,24 +1,35 @@
 use criterion::{criterion_group, criterion_main, Criterion};
-use stutter::Stutter;
+use stutter::{Settings, Stutter};
+
+const SETTINGS: Settings = Settings {
+  on: true,
+  trigger: true,
+  auto_trigger: false,
+  mix: 0,
+  pulse: 200.,
+  duration: 0.5,
+  chance: 1.,
+  is_momentary_trigger: true,
+};
 
 fn generate_signal() -> f32 {
   fastrand::f32() * 2. - 1.
 }
 
-fn generate_signal_stream(length: usize) -> Vec<(f32, f32)> {
+fn generate_signal_stream(length: usize) -> Vec<[f32; 2]> {
   (0..length)
-    .map(|_| (generate_signal(), generate_signal()))
+    .map(|_| [generate_signal(), generate_signal()])
     .collect()
 }
 
 fn stutter_bench(c: &mut Criterion) {
-  let mut stutter = Stutter::new(44100.);
+  let mut stutter = Stutter::<2>::new(44100.);
   let signal_stream = generate_signal_stream(44100);
 
   c.bench_function("stutter", |b| {
     b.iter(|| {
       for signal in &signal_stream {
-        stutter.process(*signal, true, true, false, 0, 200., 0.5, 1., true);
+        stutter.process(*signal, 0., &SETTINGS);
       }
     })
   });
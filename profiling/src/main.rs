@@ -1,14 +1,25 @@
-use stutter::Stutter;
+use stutter::{Settings, Stutter};
+
+const SETTINGS: Settings = Settings {
+  on: true,
+  trigger: true,
+  auto_trigger: false,
+  mix: 0,
+  pulse: 200.,
+  duration: 0.5,
+  chance: 1.,
+  is_momentary_trigger: true,
+};
 
 fn generate_signal() -> f32 {
   fastrand::f32() * 2. - 1.
 }
 
 fn main() {
-  let mut stutter = Stutter::new(44100.);
+  let mut stutter = Stutter::<2>::new(44100.);
 
   loop {
-    let input = (generate_signal(), generate_signal());
-    stutter.process(input, true, true, false, 0, 200., 0.5, 1., true);
+    let input = [generate_signal(), generate_signal()];
+    stutter.process(input, 0., &SETTINGS);
   }
 }
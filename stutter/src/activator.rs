@@ -1,4 +1,4 @@
-use crate::{crossfade::Crossfade, shared::tuple_ext::TupleExt};
+use crate::{crossfade::Crossfade, shared::array_ext::ArrayExt};
 
 pub struct Activator {
   is_active: bool,
@@ -13,17 +13,21 @@ impl Activator {
     }
   }
 
-  pub fn process(
+  pub fn is_active(&self) -> bool {
+    self.is_active
+  }
+
+  pub fn process<const N: usize>(
     &mut self,
-    dry_signal: (f32, f32),
-    wet_signal: (f32, f32),
+    dry_signal: [f32; N],
+    wet_signal: [f32; N],
     on: bool,
     chance: f32,
     auto_trigger: bool,
     trigger: bool,
     manual_trigger: bool,
     mix: i32,
-  ) -> (f32, f32) {
+  ) -> [f32; N] {
     if trigger {
       if auto_trigger && !manual_trigger {
         let random = fastrand::f32();
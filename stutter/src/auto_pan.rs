@@ -0,0 +1,203 @@
+use std::array;
+
+const SWEEP_STEPS: usize = 8;
+
+/// How the stutter moves across the stereo field on every repeat. The discriminants match the
+/// values of the pan pattern parameter.
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PanPattern {
+  Off = 0,
+  Alternate = 1,
+  Random = 2,
+  Sweep = 3,
+}
+
+impl From<i32> for PanPattern {
+  fn from(value: i32) -> Self {
+    match value {
+      1 => Self::Alternate,
+      2 => Self::Random,
+      3 => Self::Sweep,
+      _ => Self::Off,
+    }
+  }
+}
+
+/// The position of a channel from left (-1) to right (1) and the channel it forms a stereo pair
+/// with, which the width is applied to.
+#[derive(Clone, Copy)]
+struct Channel {
+  position: f32,
+  pair: Option<usize>,
+}
+
+pub struct AutoPan<const N: usize> {
+  channels: [Channel; N],
+  pattern: PanPattern,
+  amount: f32,
+  width: f32,
+  repeat_index: usize,
+  target: f32,
+  pan: f32,
+  smooth_coefficient: f32,
+}
+
+impl<const N: usize> AutoPan<N> {
+  pub fn new(sample_rate: f32) -> Self {
+    Self {
+      channels: array::from_fn(Self::get_channel),
+      pattern: PanPattern::Off,
+      amount: 0.,
+      width: 1.,
+      repeat_index: 0,
+      target: 0.,
+      pan: 0.,
+      smooth_coefficient: 1. - (-1. / (sample_rate * 0.005)).exp(),
+    }
+  }
+
+  pub fn set_parameters(&mut self, pattern: PanPattern, amount: f32, width: f32) {
+    self.pattern = pattern;
+    self.amount = amount;
+    self.width = width;
+  }
+
+  pub fn process(&mut self, input: [f32; N], trigger: bool, repeat_trigger: bool) -> [f32; N] {
+    if trigger {
+      self.repeat_index = 0;
+      self.target = self.get_position();
+    } else if repeat_trigger {
+      self.repeat_index = self.repeat_index.wrapping_add(1);
+      self.target = self.get_position();
+    }
+
+    let target = if self.pattern == PanPattern::Off {
+      0.
+    } else {
+      self.target * self.amount
+    };
+    self.pan += (target - self.pan) * self.smooth_coefficient;
+
+    // The pan works like a regular balance control on every left & right channel
+    array::from_fn(|i| {
+      let Channel { position, pair } = self.channels[i];
+      let widened = match pair {
+        Some(pair) => {
+          let mid = (input[i] + input[pair]) * 0.5;
+          mid + (input[i] - mid) * self.width
+        }
+        None => input[i],
+      };
+      widened * (1. + self.pan * position).min(1.)
+    })
+  }
+
+  // Mono is left untouched. In 5.1 (L R C LFE Ls Rs) the surrounds pan along with the fronts,
+  // while the center and LFE stay where they are. Other layouts aren't panned.
+  fn get_channel(index: usize) -> Channel {
+    match (N, index) {
+      (2, 0) | (6, 0) | (6, 4) => Channel {
+        position: -1.,
+        pair: Some(index + 1),
+      },
+      (2, 1) | (6, 1) | (6, 5) => Channel {
+        position: 1.,
+        pair: Some(index - 1),
+      },
+      _ => Channel {
+        position: 0.,
+        pair: None,
+      },
+    }
+  }
+
+  fn get_position(&self) -> f32 {
+    match self.pattern {
+      PanPattern::Alternate => {
+        if self.repeat_index.is_multiple_of(2) {
+          -1.
+        } else {
+          1.
+        }
+      }
+      PanPattern::Random => fastrand::f32() * 2. - 1.,
+      PanPattern::Sweep => {
+        let step = (self.repeat_index % SWEEP_STEPS) as f32 * 2. / SWEEP_STEPS as f32;
+        let triangle = if step <= 1. { step } else { 2. - step };
+        triangle * 2. - 1.
+      }
+      PanPattern::Off => 0.,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{AutoPan, PanPattern};
+
+  #[test]
+  fn alternate_pattern_moves_between_sides_per_repeat() {
+    let mut auto_pan = AutoPan::<2>::new(1000.);
+    auto_pan.set_parameters(PanPattern::Alternate, 1., 1.);
+
+    assert_eq!(auto_pan.get_position(), -1.);
+    auto_pan.process([1., 1.], false, true);
+    assert_eq!(auto_pan.get_position(), 1.);
+    auto_pan.process([1., 1.], true, true);
+    assert_eq!(auto_pan.get_position(), -1.);
+  }
+
+  #[test]
+  fn sweep_pattern_follows_a_triangle() {
+    let mut auto_pan = AutoPan::<2>::new(1000.);
+    auto_pan.set_parameters(PanPattern::Sweep, 1., 1.);
+
+    let positions: Vec<f32> = (0..9)
+      .map(|_| {
+        let position = auto_pan.get_position();
+        auto_pan.process([0., 0.], false, true);
+        position
+      })
+      .collect();
+    assert_eq!(positions, vec![-1., -0.5, 0., 0.5, 1., 0.5, 0., -0.5, -1.]);
+  }
+
+  #[test]
+  fn zero_width_sums_to_mono() {
+    let mut auto_pan = AutoPan::<2>::new(1000.);
+    auto_pan.set_parameters(PanPattern::Off, 0., 0.);
+
+    assert_eq!(auto_pan.process([1., 0.], false, false), [0.5, 0.5]);
+  }
+
+  #[test]
+  fn surround_center_and_lfe_are_not_panned() {
+    let mut auto_pan = AutoPan::<6>::new(1000.);
+    auto_pan.set_parameters(PanPattern::Alternate, 1., 0.);
+
+    // The alternate pattern pans to the left on the first repeat
+    let mut output = [0.; 6];
+    for _ in 0..1000 {
+      output = auto_pan.process([1., 0.5, 1., 1., 1., 0.5], true, false);
+    }
+
+    // A zero width sums each left & right pair, and only the right channels are attenuated
+    assert_eq!(output[0], 0.75);
+    assert!(output[1] < 0.001);
+    assert_eq!(output[2], 1.);
+    assert_eq!(output[3], 1.);
+    assert_eq!(output[4], 0.75);
+    assert!(output[5] < 0.001);
+  }
+
+  #[test]
+  fn mono_signal_is_not_panned() {
+    let mut auto_pan = AutoPan::<1>::new(1000.);
+    auto_pan.set_parameters(PanPattern::Alternate, 1., 0.);
+
+    for _ in 0..100 {
+      assert_eq!(auto_pan.process([1.], false, false), [1.]);
+    }
+  }
+}
@@ -0,0 +1,170 @@
+// Intervals that deviate more than this from the smoothed interval are treated as a tempo change.
+const JUMP_THRESHOLD: f32 = 0.5;
+const SMOOTHING: f32 = 0.2;
+const MIDI_CLOCK_PPQN: f32 = 24.;
+
+#[derive(Clone, Copy, PartialEq)]
+enum ClockSource {
+  Midi,
+  Gate,
+}
+
+/// Measures the period of an incoming clock, like MIDI clock or CV pulses, and converts it to a
+/// pulse time. Clock ticks are passed with their sample offset in the current block. Every source
+/// has its own amount of ticks per quarter note, and a tick is only measured against the previous
+/// tick of the same source.
+pub struct ClockFollower {
+  time: u64,
+  last_tick: Option<(u64, ClockSource)>,
+  quarter_note: Option<f32>,
+  is_gate_high: bool,
+  gate_ppqn: f32,
+  sample_rate: f32,
+}
+
+impl ClockFollower {
+  pub fn new(sample_rate: f32) -> Self {
+    Self {
+      time: 0,
+      last_tick: None,
+      quarter_note: None,
+      is_gate_high: false,
+      gate_ppqn: 24.,
+      sample_rate,
+    }
+  }
+
+  /// Sets the amount of ticks per quarter note of the clock gate. MIDI clock always runs at 24
+  /// PPQN.
+  pub fn set_ppqn(&mut self, ppqn: f32) {
+    self.gate_ppqn = ppqn.max(1.);
+  }
+
+  /// Registers a MIDI clock message.
+  pub fn tick(&mut self, offset: u32) {
+    self.register_tick(offset, ClockSource::Midi, MIDI_CLOCK_PPQN);
+  }
+
+  /// Detects rising edges of a clock gate, like a CV input.
+  pub fn process_gate(&mut self, is_high: bool, offset: u32) {
+    if is_high && !self.is_gate_high {
+      self.register_tick(offset, ClockSource::Gate, self.gate_ppqn);
+    }
+    self.is_gate_high = is_high;
+  }
+
+  /// Forgets the previous tick, so a clock that restarts isn't measured against a stale tick.
+  pub fn restart(&mut self) {
+    self.last_tick = None;
+  }
+
+  /// Moves the clock forward by the length of the processed block.
+  pub fn advance(&mut self, samples: u32) {
+    self.time += samples as u64;
+  }
+
+  /// Returns the length of a quarter note in milliseconds, or None until two ticks have arrived.
+  pub fn get_pulse_time(&self) -> Option<f32> {
+    self
+      .quarter_note
+      .map(|quarter_note| quarter_note / self.sample_rate * 1000.)
+  }
+
+  fn register_tick(&mut self, offset: u32, source: ClockSource, ppqn: f32) {
+    let now = self.time + offset as u64;
+
+    if let Some((last_tick, _)) = self
+      .last_tick
+      .filter(|(_, last_source)| *last_source == source)
+    {
+      let quarter_note = now.saturating_sub(last_tick) as f32 * ppqn;
+      if quarter_note > 0. {
+        self.quarter_note = Some(match self.quarter_note {
+          Some(smoothed) if ((quarter_note - smoothed) / smoothed).abs() <= JUMP_THRESHOLD => {
+            smoothed + (quarter_note - smoothed) * SMOOTHING
+          }
+          _ => quarter_note,
+        });
+      }
+    }
+    self.last_tick = Some((now, source));
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::ClockFollower;
+
+  fn follow(clock_follower: &mut ClockFollower, interval: u32, ticks: usize) {
+    for _ in 0..ticks {
+      clock_follower.tick(0);
+      clock_follower.advance(interval);
+    }
+  }
+
+  fn pulse(clock_follower: &mut ClockFollower) {
+    clock_follower.process_gate(true, 0);
+    clock_follower.process_gate(false, 0);
+  }
+
+  #[test]
+  fn should_convert_midi_clock_to_a_pulse_time() {
+    let mut clock_follower = ClockFollower::new(48000.);
+    assert_eq!(clock_follower.get_pulse_time(), None);
+
+    // 120 BPM at 24 PPQN is one tick every 1000 samples
+    follow(&mut clock_follower, 1000, 4);
+    assert_eq!(clock_follower.get_pulse_time(), Some(500.));
+  }
+
+  #[test]
+  fn should_keep_midi_clock_at_24_ppqn() {
+    let mut clock_follower = ClockFollower::new(48000.);
+    clock_follower.set_ppqn(4.);
+
+    follow(&mut clock_follower, 1000, 4);
+    assert_eq!(clock_follower.get_pulse_time(), Some(500.));
+  }
+
+  #[test]
+  fn should_smooth_jitter_and_follow_tempo_changes() {
+    let mut clock_follower = ClockFollower::new(1000.);
+    clock_follower.set_ppqn(1.);
+    for interval in [500, 500, 500, 500, 550] {
+      pulse(&mut clock_follower);
+      clock_follower.advance(interval);
+    }
+    pulse(&mut clock_follower);
+    assert_eq!(clock_follower.get_pulse_time(), Some(510.));
+
+    clock_follower.advance(1000);
+    pulse(&mut clock_follower);
+    assert_eq!(clock_follower.get_pulse_time(), Some(1000.));
+  }
+
+  #[test]
+  fn should_not_measure_between_different_sources() {
+    let mut clock_follower = ClockFollower::new(1000.);
+    clock_follower.set_ppqn(1.);
+    for _ in 0..3 {
+      pulse(&mut clock_follower);
+      clock_follower.advance(500);
+    }
+
+    // A MIDI clock tick right after a gate tick isn't a 24th of a quarter note
+    clock_follower.advance(10);
+    clock_follower.tick(0);
+    assert_eq!(clock_follower.get_pulse_time(), Some(500.));
+  }
+
+  #[test]
+  fn should_detect_rising_edges_of_a_gate() {
+    let mut clock_follower = ClockFollower::new(1000.);
+    clock_follower.set_ppqn(1.);
+
+    for i in 0..1000 {
+      clock_follower.process_gate(i % 250 < 10, i);
+    }
+    assert_eq!(clock_follower.get_pulse_time(), Some(250.));
+  }
+}
@@ -1,32 +1,50 @@
 use crate::{
-  shared::tuple_ext::TupleExt,
-  stereo_delay_line::{Interpolation, StereoDelayLine},
+  delay_line::{DelayLine, Interpolation},
+  shared::array_ext::ArrayExt,
 };
 
-pub struct Delay {
+pub struct Delay<const N: usize> {
   delay_time: f32,
-  delay_line: StereoDelayLine,
+  time_fraction: f32,
+  is_stretching: bool,
+  smooth_coefficient: f32,
+  delay_line: DelayLine<N>,
 }
 
-impl Delay {
+impl<const N: usize> Delay<N> {
   pub fn new(sample_rate: f32, length: usize) -> Self {
     Self {
       delay_time: 1.,
-      delay_line: StereoDelayLine::new(length, sample_rate),
+      time_fraction: 1.,
+      is_stretching: false,
+      smooth_coefficient: 1. - (-1. / (sample_rate * 0.05)).exp(),
+      delay_line: DelayLine::new(length, sample_rate),
     }
   }
 
+  /// When stretching, the slice follows tempo changes by gliding to the new delay time, which
+  /// briefly bends the pitch like a tape machine. Otherwise the slice keeps the length it was
+  /// captured with.
+  pub fn set_stretch(&mut self, is_stretching: bool) {
+    self.is_stretching = is_stretching;
+  }
+
   pub fn process(
     &mut self,
-    input: (f32, f32),
+    input: [f32; N],
     trigger: bool,
-    delay_time: f32,
+    pulse: f32,
+    time_fraction: f32,
     fade_a: f32,
     fade_b: f32,
-  ) -> (f32, f32) {
+  ) -> [f32; N] {
     if trigger {
-      self.delay_time = delay_time;
-    };
+      self.time_fraction = time_fraction;
+      self.delay_time = pulse * time_fraction;
+    } else if self.is_stretching {
+      let target = pulse * self.time_fraction;
+      self.delay_time += (target - self.delay_time) * self.smooth_coefficient;
+    }
 
     let delay_out = self
       .delay_line
@@ -40,6 +58,14 @@ impl Delay {
     delay_out
   }
 
+  pub fn get_delay_time(&self) -> f32 {
+    self.delay_time
+  }
+
+  pub fn get_time_fraction(&self) -> f32 {
+    self.time_fraction
+  }
+
   pub fn get_write_index(&self) -> usize {
     self.delay_line.get_write_index()
   }
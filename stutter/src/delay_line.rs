@@ -1,4 +1,4 @@
-use std::f32::consts::PI;
+use std::{array, f32::consts::PI};
 
 #[allow(dead_code)]
 #[derive(Clone, Copy)]
@@ -11,25 +11,25 @@ pub enum Interpolation {
 }
 
 #[derive(Clone)]
-pub struct StereoDelayLine {
-  buffer: Vec<(f32, f32)>,
+pub struct DelayLine<const N: usize> {
+  buffer: Vec<[f32; N]>,
   write_pointer: usize,
   sample_rate: f32,
   wrap: usize,
 }
 
-impl StereoDelayLine {
+impl<const N: usize> DelayLine<N> {
   pub fn new(length: usize, sample_rate: f32) -> Self {
     let size = length.next_power_of_two();
     Self {
-      buffer: vec![(0.0, 0.0); size],
+      buffer: vec![[0.0; N]; size],
       write_pointer: 0,
       sample_rate,
       wrap: size - 1,
     }
   }
 
-  pub fn read(&self, time: f32, interp: Interpolation) -> (f32, f32) {
+  pub fn read(&self, time: f32, interp: Interpolation) -> [f32; N] {
     match interp {
       Interpolation::Step => self.step_interp(time),
       Interpolation::Linear => self.linear_interp(time),
@@ -39,7 +39,7 @@ impl StereoDelayLine {
     }
   }
 
-  pub fn write(&mut self, value: (f32, f32)) {
+  pub fn write(&mut self, value: [f32; N]) {
     self.buffer[self.write_pointer] = value;
     self.write_pointer = self.write_pointer + 1 & self.wrap;
   }
@@ -48,7 +48,7 @@ impl StereoDelayLine {
     self.write_pointer
   }
 
-  fn step_interp(&self, time: f32) -> (f32, f32) {
+  fn step_interp(&self, time: f32) -> [f32; N] {
     let read_pointer =
       (self.write_pointer + self.buffer.len()) as f32 - (self.mstosamps(time) - 0.5).max(1.);
     let index = read_pointer.trunc() as usize;
@@ -56,7 +56,7 @@ impl StereoDelayLine {
     self.buffer[index & self.wrap]
   }
 
-  fn linear_interp(&self, time: f32) -> (f32, f32) {
+  fn linear_interp(&self, time: f32) -> [f32; N] {
     let read_pointer =
       (self.write_pointer + self.buffer.len()) as f32 - self.mstosamps(time).max(1.);
     let rounded_read_pointer = read_pointer.trunc();
@@ -65,10 +65,10 @@ impl StereoDelayLine {
 
     let x = self.buffer[index & self.wrap];
     let y = self.buffer[index + 1 & self.wrap];
-    (x.0 + (y.0 - x.0) * mix, x.1 + (y.1 - x.1) * mix)
+    array::from_fn(|i| x[i] + (y[i] - x[i]) * mix)
   }
 
-  fn cosine_interp(&self, time: f32) -> (f32, f32) {
+  fn cosine_interp(&self, time: f32) -> [f32; N] {
     let read_pointer =
       (self.write_pointer + self.buffer.len()) as f32 - self.mstosamps(time).max(1.);
     let rounded_read_pointer = read_pointer.trunc();
@@ -78,13 +78,10 @@ impl StereoDelayLine {
     let cosine_mix = (1. - (mix * PI).cos()) / 2.;
     let x = self.buffer[index & self.wrap];
     let y = self.buffer[index + 1 & self.wrap];
-    (
-      x.0 + (y.0 - x.0) * cosine_mix,
-      x.1 + (y.1 - x.1) * cosine_mix,
-    )
+    array::from_fn(|i| x[i] + (y[i] - x[i]) * cosine_mix)
   }
 
-  fn cubic_interp(&self, time: f32) -> (f32, f32) {
+  fn cubic_interp(&self, time: f32) -> [f32; N] {
     let read_pointer =
       (self.write_pointer + self.buffer.len()) as f32 - self.mstosamps(time).max(2.);
     let rounded_read_pointer = read_pointer.trunc();
@@ -105,13 +102,10 @@ impl StereoDelayLine {
     let fx = 0.5 * bb * a1;
     let fy = 0.5 * aa * b1;
     let fz = -0.1666667 * aa * b;
-    (
-      w.0 * fw + x.0 * fx + y.0 * fy + z.0 * fz,
-      w.1 * fw + x.1 * fx + y.1 * fy + z.1 * fz,
-    )
+    array::from_fn(|i| w[i] * fw + x[i] * fx + y[i] * fy + z[i] * fz)
   }
 
-  fn spline_interp(&self, time: f32) -> (f32, f32) {
+  fn spline_interp(&self, time: f32) -> [f32; N] {
     let read_pointer =
       (self.write_pointer + self.buffer.len()) as f32 - self.mstosamps(time).max(2.);
     let rounded_read_pointer = read_pointer.trunc();
@@ -123,20 +117,13 @@ impl StereoDelayLine {
     let y = self.buffer[index + 2 & self.wrap];
     let z = self.buffer[index + 3 & self.wrap];
 
-    let c0 = x;
-    let c1 = (0.5 * (y.0 - w.0), 0.5 * (y.1 - w.1));
-    let c2 = (
-      w.0 - 2.5 * x.0 + y.0 + y.0 - 0.5 * z.0,
-      w.1 - 2.5 * x.1 + y.1 + y.1 - 0.5 * z.1,
-    );
-    let c3 = (
-      0.5 * (z.0 - w.0) + 1.5 * (x.0 - y.0),
-      0.5 * (z.1 - w.1) + 1.5 * (x.1 - y.1),
-    );
-    (
-      ((c3.0 * mix + c2.0) * mix + c1.0) * mix + c0.0,
-      ((c3.1 * mix + c2.1) * mix + c1.1) * mix + c0.1,
-    )
+    array::from_fn(|i| {
+      let c0 = x[i];
+      let c1 = 0.5 * (y[i] - w[i]);
+      let c2 = w[i] - 2.5 * x[i] + y[i] + y[i] - 0.5 * z[i];
+      let c3 = 0.5 * (z[i] - w[i]) + 1.5 * (x[i] - y[i]);
+      ((c3 * mix + c2) * mix + c1) * mix + c0
+    })
   }
 
   fn mstosamps(&self, time: f32) -> f32 {
@@ -1,10 +1,24 @@
 pub struct DurationGenerator {
   fraction: f32,
+  forced_fraction: Option<f32>,
 }
 
 impl DurationGenerator {
   pub fn new() -> Self {
-    Self { fraction: 1. }
+    Self {
+      fraction: 1.,
+      forced_fraction: None,
+    }
+  }
+
+  pub fn get_fraction(&self) -> f32 {
+    self.fraction
+  }
+
+  /// Makes the next manual trigger repeat its slice this many times before the next auto trigger,
+  /// instead of a random amount.
+  pub fn force_fraction(&mut self, fraction: f32) {
+    self.forced_fraction = Some(fraction.max(1.));
   }
 
   pub fn process(
@@ -13,10 +27,19 @@ impl DurationGenerator {
     time_fraction: f32,
     duration: f32,
     trigger: bool,
+    is_manual_trigger: bool,
   ) -> f32 {
     if trigger {
-      let random = fastrand::f32();
-      self.fraction = (random * duration * 8. / time_fraction).ceil().max(1.);
+      self.fraction = match self.forced_fraction {
+        Some(fraction) if is_manual_trigger => {
+          self.forced_fraction = None;
+          fraction
+        }
+        _ => {
+          let random = fastrand::f32();
+          (random * duration * 8. / time_fraction).ceil().max(1.)
+        }
+      };
     }
 
     delay_time * self.fraction
@@ -1,39 +1,68 @@
 mod activator;
+mod auto_pan;
+mod clock_follower;
 mod crossfade;
 mod delay;
+mod delay_line;
 mod duration_generator;
 mod manual_trigger;
+pub mod note_values;
+mod onset_detector;
 mod phasor;
+pub mod presets;
+pub mod probability_randomizer;
 mod repeat_trigger;
-mod stereo_delay_line;
-mod time_fraction_generator;
+mod settings;
+pub mod sync;
+mod tap_tempo;
+pub mod tempo_factor;
+pub mod time_fraction_generator;
 mod toggle_trigger;
+mod transient_quantizer;
+mod trigger_event;
 mod shared {
+  pub mod array_ext;
   pub mod float_ext;
-  pub mod tuple_ext;
 }
 use {
-  crate::repeat_trigger::RepeatTrigger, activator::Activator, crossfade::Crossfade, delay::Delay,
-  duration_generator::DurationGenerator, manual_trigger::ManualTrigger, phasor::Phasor,
-  shared::tuple_ext::TupleExt, time_fraction_generator::TimeFractionGenerator,
-  toggle_trigger::ToggleTrigger,
+  crate::repeat_trigger::RepeatTrigger, activator::Activator, auto_pan::AutoPan,
+  crossfade::Crossfade, delay::Delay, duration_generator::DurationGenerator,
+  manual_trigger::ManualTrigger, note_values::NOTE_VALUES, onset_detector::OnsetDetector,
+  phasor::Phasor, shared::array_ext::ArrayExt, std::array,
+  time_fraction_generator::TimeFractionGenerator, toggle_trigger::ToggleTrigger,
+  transient_quantizer::TransientQuantizer,
+};
+pub use {
+  auto_pan::PanPattern, clock_follower::ClockFollower, settings::Settings, tap_tempo::TapTempo,
+  trigger_event::TriggerEvent,
 };
 
-pub struct Stutter {
+pub struct Stutter<const N: usize> {
   time_fraction_generator: TimeFractionGenerator,
   duration_generator: DurationGenerator,
   manual_trigger: ManualTrigger,
+  onset_detector: OnsetDetector,
+  is_sidechain_enabled: bool,
   toggle_trigger: ToggleTrigger,
+  transient_quantizer: TransientQuantizer,
+  capture_fraction: f32,
   duration: f32,
   phasor: Phasor,
   repeat_trigger: RepeatTrigger,
   flip_flop: bool,
+  is_frozen: bool,
+  is_trigger_requested: bool,
+  repeat_count: u32,
+  time: u64,
+  last_trigger: Option<TriggerEvent>,
   delay_crossfade: Crossfade,
-  delay: [Delay; 2],
+  live_crossfade: Crossfade,
+  delay: [Delay<N>; 2],
+  auto_pan: AutoPan<N>,
   activator: Activator,
 }
 
-impl Stutter {
+impl<const N: usize> Stutter<N> {
   pub fn new(sample_rate: f32) -> Self {
     let delay_length = (sample_rate * 12.) as usize;
 
@@ -41,16 +70,27 @@ impl Stutter {
       time_fraction_generator: TimeFractionGenerator::new(),
       duration_generator: DurationGenerator::new(),
       manual_trigger: ManualTrigger::new(),
+      onset_detector: OnsetDetector::new(sample_rate),
+      is_sidechain_enabled: false,
       toggle_trigger: ToggleTrigger::new(),
+      transient_quantizer: TransientQuantizer::new(sample_rate),
+      capture_fraction: 1.,
       duration: 0.,
       phasor: Phasor::new(sample_rate),
       repeat_trigger: RepeatTrigger::new(sample_rate, delay_length),
       flip_flop: false,
+      is_frozen: false,
+      is_trigger_requested: false,
+      repeat_count: 0,
+      time: 0,
+      last_trigger: None,
       delay_crossfade: Crossfade::new(sample_rate),
+      live_crossfade: Crossfade::new(sample_rate),
       delay: [
         Delay::new(sample_rate, delay_length),
         Delay::new(sample_rate, delay_length),
       ],
+      auto_pan: AutoPan::new(sample_rate),
       activator: Activator::new(sample_rate),
     }
   }
@@ -73,69 +113,184 @@ impl Stutter {
     thirty_second_triplet_notes: f32,
     sixty_fourth_notes: f32,
   ) {
-    self.time_fraction_generator.set_probability([
-      (half_notes, 2.),
-      (seven_sixteenth_notes, 1.75),
-      (six_sixteenth_notes, 1.5),
-      (half_triplet_notes, 1.33333333),
-      (five_sixteenth_notes, 1.25),
-      (quarter_notes, 1.),
-      (three_sixteenth_notes, 0.75),
-      (quarter_triplet_notes, 1.5_f32.recip()),
-      (eighth_notes, 2_f32.recip()),
-      (eighth_triplet_notes, 3_f32.recip()),
-      (sixteenth_notes, 4_f32.recip()),
-      (sixteenth_triplet_notes, 6_f32.recip()),
-      (thirty_second_notes, 8_f32.recip()),
-      (thirty_second_triplet_notes, 12_f32.recip()),
-      (sixty_fourth_notes, 16_f32.recip()),
-    ]);
+    let probability = [
+      half_notes,
+      seven_sixteenth_notes,
+      six_sixteenth_notes,
+      half_triplet_notes,
+      five_sixteenth_notes,
+      quarter_notes,
+      three_sixteenth_notes,
+      quarter_triplet_notes,
+      eighth_notes,
+      eighth_triplet_notes,
+      sixteenth_notes,
+      sixteenth_triplet_notes,
+      thirty_second_notes,
+      thirty_second_triplet_notes,
+      sixty_fourth_notes,
+    ];
+    self
+      .time_fraction_generator
+      .set_probability(array::from_fn(|index| {
+        (probability[index], NOTE_VALUES[index].1)
+      }));
+  }
+
+  /// Biases the note probabilities with the triplet & odd balance and the short to long tilt,
+  /// which all range from -1 to 1. Call this before `set_probability`.
+  pub fn set_probability_macros(&mut self, triplet_balance: f32, odd_balance: f32, tilt: f32) {
+    self
+      .time_fraction_generator
+      .set_bias(triplet_balance, odd_balance, tilt);
+  }
+
+  pub fn set_auto_pan(&mut self, pattern: PanPattern, amount: f32, width: f32) {
+    self.auto_pan.set_parameters(pattern, amount, width);
+  }
+
+  pub fn set_sidechain(&mut self, enabled: bool, sensitivity: f32, hold_off: f32) {
+    self.is_sidechain_enabled = enabled;
+    self.onset_detector.set_parameters(sensitivity, hold_off);
+  }
+
+  /// Lets slices follow tempo changes instead of keeping the length they were captured with.
+  pub fn set_time_stretch(&mut self, is_stretching: bool) {
+    self
+      .delay
+      .iter_mut()
+      .for_each(|delay| delay.set_stretch(is_stretching));
+  }
+
+  /// Keeps repeating the current slice by ignoring auto and sidechain triggers. Manual triggers
+  /// still capture a new slice.
+  pub fn set_freeze(&mut self, is_frozen: bool) {
+    self.is_frozen = is_frozen;
+  }
+
+  /// Makes the next manual trigger use this time fraction instead of a random one.
+  pub fn force_time_fraction(&mut self, time_fraction: f32) {
+    self.time_fraction_generator.force_fraction(time_fraction);
+  }
+
+  /// Triggers on the next sample with the given time fraction instead of a random one. The
+  /// duration multiple sets how many times the slice plays before the next auto trigger, or is
+  /// random like on any other trigger when it's None. Auto triggers keep picking at random.
+  pub fn trigger_with(&mut self, time_fraction: f32, duration_multiple: Option<f32>) {
+    self.force_time_fraction(time_fraction);
+    if let Some(duration_multiple) = duration_multiple {
+      self.duration_generator.force_fraction(duration_multiple);
+    }
+    self.is_trigger_requested = true;
+  }
+
+  /// Lets every trigger wait up to `window` milliseconds for the next transient in the input, so
+  /// slices start on an attack. The first pass of such a slice plays the input, because the slice
+  /// can't be captured before it has been recorded. A window of zero disables this.
+  pub fn set_transient_quantize(&mut self, window: f32, sensitivity: f32) {
+    self.transient_quantizer.set_parameters(window, sensitivity);
   }
 
   pub fn process(
     &mut self,
-    input: (f32, f32),
-    on: bool,
-    manual_trigger: bool,
-    auto_trigger: bool,
-    mix: i32,
-    pulse: f32,
-    duration: f32,
-    chance: f32,
-    is_momentary_trigger: bool,
-  ) -> (f32, f32, bool, bool) {
+    input: [f32; N],
+    sidechain: f32,
+    settings: &Settings,
+  ) -> ([f32; N], bool, bool) {
+    let Settings {
+      on,
+      trigger: manual_trigger,
+      auto_trigger,
+      mix,
+      pulse,
+      duration,
+      chance,
+      is_momentary_trigger,
+    } = *settings;
+    let sidechain_trigger =
+      self.onset_detector.process(sidechain) && self.is_sidechain_enabled && !self.is_frozen;
     let manual_trigger = self
       .manual_trigger
-      .process(manual_trigger, is_momentary_trigger);
+      .process(manual_trigger, is_momentary_trigger)
+      || sidechain_trigger
+      || std::mem::take(&mut self.is_trigger_requested);
     let reset = self.toggle_trigger.process(on) || manual_trigger;
     if reset {
       self.phasor.reset();
     }
-    let (trigger, (trigger_a, trigger_b)) = self.get_triggers(auto_trigger, reset);
+    let requested_trigger =
+      reset || (auto_trigger && !self.is_frozen && self.phasor.process(self.duration));
 
-    let time_fraction = self.time_fraction_generator.process(trigger);
+    let time_fraction = self
+      .time_fraction_generator
+      .process(requested_trigger, manual_trigger);
     let delay_time = pulse * time_fraction;
 
-    self.duration = self
-      .duration_generator
-      .process(delay_time, time_fraction, duration, trigger);
+    let (trigger, manual_trigger, capture) = self.transient_quantizer.process(
+      input.iter().sum::<f32>() / N as f32,
+      requested_trigger,
+      manual_trigger,
+      delay_time,
+    );
+    // A trigger that waits for a transient may be captured after the next one picked a new time
+    // fraction, so the slice keeps the fraction it was triggered with.
+    if trigger {
+      self.capture_fraction = time_fraction;
+    }
+    let (trigger_a, trigger_b) = self.get_triggers(capture);
+
+    self.duration =
+      self
+        .duration_generator
+        .process(delay_time, time_fraction, duration, trigger, manual_trigger);
 
     let (delay_fade_a, delay_fade_b) = self
       .delay_crossfade
       .process(self.flip_flop, 20_f32.min(delay_time * 0.5));
     let delay_out = self.delay[0]
-      .process(input, trigger_a, delay_time, delay_fade_a, delay_fade_b)
-      .add(self.delay[1].process(input, trigger_b, delay_time, delay_fade_b, delay_fade_a));
+      .process(
+        input,
+        trigger_a,
+        pulse,
+        self.capture_fraction,
+        delay_fade_a,
+        delay_fade_b,
+      )
+      .add(self.delay[1].process(
+        input,
+        trigger_b,
+        pulse,
+        self.capture_fraction,
+        delay_fade_b,
+        delay_fade_a,
+      ));
 
+    // The first pass of a slice that isn't captured yet is the input itself
+    let is_playing_live = self.transient_quantizer.is_capturing();
+    let (live_fade, slice_fade) = self
+      .live_crossfade
+      .process(is_playing_live, 20_f32.min(delay_time * 0.5));
+    let wet_out = delay_out
+      .multiply_with(slice_fade)
+      .add(input.multiply_with(live_fade));
+
+    // The capture counts as the first repeat of a slice that started on a transient
+    let repeat_trigger = self
+      .repeat_trigger
+      .process(&self.delay, capture, self.flip_flop)
+      && !is_playing_live;
+    if trigger {
+      self.repeat_count = 0;
+    } else if repeat_trigger {
+      self.repeat_count += 1;
+    }
     let trigger_cv_output = on && trigger;
-    let repeat_trigger_cv_output = on
-      && self
-        .repeat_trigger
-        .process(&self.delay, trigger, self.flip_flop, delay_time);
+    let repeat_trigger_cv_output = on && repeat_trigger;
 
+    let panned_delay_out = self.auto_pan.process(wet_out, trigger, repeat_trigger);
     let stutter_output = self.activator.process(
       input,
-      delay_out,
+      panned_delay_out,
       on,
       chance,
       auto_trigger,
@@ -143,30 +298,339 @@ impl Stutter {
       manual_trigger,
       mix,
     );
+
+    if trigger {
+      self.last_trigger = Some(TriggerEvent {
+        time: self.time,
+        time_fraction: self.capture_fraction,
+        duration_multiple: self.duration_generator.get_fraction(),
+        is_active: on && self.activator.is_active(),
+      });
+    }
+    self.time += 1;
+
     (
-      stutter_output.0,
-      stutter_output.1,
+      stutter_output,
       trigger_cv_output,
       trigger_cv_output || repeat_trigger_cv_output,
     )
   }
 
-  fn get_triggers(&mut self, auto_trigger: bool, reset: bool) -> (bool, (bool, bool)) {
-    let trigger = reset || (auto_trigger && self.phasor.process(self.duration));
+  /// Returns the length of the current slice in milliseconds.
+  pub fn get_slice_length(&self) -> f32 {
+    self.get_active_delay().get_delay_time()
+  }
 
-    (
-      trigger,
-      match (trigger, self.flip_flop) {
-        (true, false) => {
-          self.flip_flop = true;
-          (true, false)
+  /// Returns the time fraction the current slice was captured with.
+  pub fn get_time_fraction(&self) -> f32 {
+    self.get_active_delay().get_time_fraction()
+  }
+
+  /// Returns how many times the current slice has been repeated since it was captured.
+  pub fn get_repeat_count(&self) -> u32 {
+    self.repeat_count
+  }
+
+  /// Returns whether the last trigger let the stutter through, which depends on the chance.
+  pub fn is_active(&self) -> bool {
+    self.activator.is_active()
+  }
+
+  /// Returns the record of the last trigger. Compare its time to tell triggers apart.
+  pub fn get_last_trigger(&self) -> Option<TriggerEvent> {
+    self.last_trigger
+  }
+
+  fn get_active_delay(&self) -> &Delay<N> {
+    &self.delay[if self.flip_flop { 0 } else { 1 }]
+  }
+
+  fn get_triggers(&mut self, trigger: bool) -> (bool, bool) {
+    match (trigger, self.flip_flop) {
+      (true, false) => {
+        self.flip_flop = true;
+        (true, false)
+      }
+      (true, true) => {
+        self.flip_flop = false;
+        (false, true)
+      }
+      _ => (false, false),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{Settings, Stutter};
+  use std::f32::consts::TAU;
+
+  const SAMPLE_RATE: f32 = 8000.;
+  const SETTINGS: Settings = Settings {
+    on: true,
+    trigger: false,
+    auto_trigger: true,
+    mix: 0,
+    pulse: 300.,
+    duration: 0.,
+    chance: 1.,
+    is_momentary_trigger: true,
+  };
+
+  struct SweepResult {
+    trigger_intervals: Vec<usize>,
+    max_step: f32,
+  }
+
+  // Runs a sine through the stutter while the tempo ramps from one bpm to another.
+  fn sweep_bpm(from: f32, to: f32, seconds: f32, is_stretching: bool) -> SweepResult {
+    fastrand::seed(1);
+    let mut stutter = Stutter::<1>::new(SAMPLE_RATE);
+    stutter.set_probability(0., 0., 0., 0., 0., 1., 0., 1., 1., 1., 1., 0., 0., 0., 0.);
+    stutter.set_time_stretch(is_stretching);
+
+    let length = (seconds * SAMPLE_RATE) as usize;
+    let mut prev_output = 0.;
+    let mut prev_trigger = None;
+    let mut result = SweepResult {
+      trigger_intervals: Vec::new(),
+      max_step: 0.,
+    };
+
+    for i in 0..length {
+      let bpm = from + (to - from) * i as f32 / length as f32;
+      let input = (i as f32 * TAU * 110. / SAMPLE_RATE).sin() * 0.5;
+      let ([output], trigger, _) = stutter.process(
+        [input],
+        0.,
+        &Settings {
+          mix: 2,
+          pulse: 60000. / bpm,
+          is_momentary_trigger: false,
+          ..SETTINGS
+        },
+      );
+
+      if trigger {
+        if let Some(prev_trigger) = prev_trigger {
+          result.trigger_intervals.push(i - prev_trigger);
         }
-        (true, true) => {
-          self.flip_flop = false;
-          (false, true)
+        prev_trigger = Some(i);
+      }
+      result.max_step = result.max_step.max((output - prev_output).abs());
+      prev_output = output;
+    }
+
+    result
+  }
+
+  #[test]
+  fn should_report_the_current_slice() {
+    let mut stutter = Stutter::<1>::new(SAMPLE_RATE);
+    stutter.set_probability(0., 0., 0., 0., 0., 0., 0., 0., 1., 0., 0., 0., 0., 0., 0.);
+
+    // A manual trigger without auto trigger keeps repeating the same slice
+    for i in 0..1000 {
+      stutter.process(
+        [0.],
+        0.,
+        &Settings {
+          trigger: i < 10,
+          auto_trigger: false,
+          pulse: 100.,
+          duration: 0.5,
+          ..SETTINGS
+        },
+      );
+    }
+    assert!(stutter.is_active());
+    assert_eq!(stutter.get_time_fraction(), 0.5);
+    assert_eq!(stutter.get_slice_length(), 50.);
+    // A slice of 50 ms lasts 400 samples, so it repeated twice after it was captured
+    assert_eq!(stutter.get_repeat_count(), 2);
+  }
+
+  #[test]
+  fn should_record_every_trigger() {
+    fastrand::seed(1);
+    let mut stutter = Stutter::<1>::new(SAMPLE_RATE);
+    stutter.set_probability(0., 0., 0., 0., 0., 0., 0., 0., 0., 1., 0., 0., 0., 0., 0.);
+    assert_eq!(stutter.get_last_trigger(), None);
+
+    let mut trigger_times = Vec::new();
+    for _ in 0..4000 {
+      let (_, trigger, _) = stutter.process(
+        [0.],
+        0.,
+        &Settings {
+          chance: 0.,
+          ..SETTINGS
+        },
+      );
+      if trigger {
+        let event = stutter.get_last_trigger().unwrap();
+        assert_eq!(event.time_fraction, 1. / 3.);
+        assert_eq!(event.duration_multiple, 1.);
+        // The chance is zero, so auto triggers are never heard
+        assert!(!event.is_active);
+        trigger_times.push(event.time);
+      }
+    }
+    // A third of 300 ms lasts 800 samples
+    assert_eq!(trigger_times, vec![0, 800, 1600, 2400, 3200]);
+  }
+
+  #[test]
+  fn should_hold_the_slice_while_frozen() {
+    let mut stutter = Stutter::<1>::new(SAMPLE_RATE);
+    stutter.set_probability(0., 0., 0., 0., 0., 0., 0., 0., 0., 1., 0., 0., 0., 0., 0.);
+    stutter.set_freeze(true);
+    stutter.force_time_fraction(0.25);
+
+    let mut trigger_count = 0;
+    for i in 0..4000 {
+      let manual_trigger = i == 2000;
+      let (_, trigger, _) = stutter.process(
+        [0.],
+        0.,
+        &Settings {
+          trigger: manual_trigger,
+          ..SETTINGS
+        },
+      );
+      if trigger {
+        trigger_count += 1;
+      }
+      if i < 2000 {
+        // Only switching on triggers, which picks a random fraction
+        assert_eq!(stutter.get_time_fraction(), 1. / 3.);
+      }
+    }
+    // The manual trigger still captures a new slice, with the forced fraction
+    assert_eq!(trigger_count, 2);
+    assert_eq!(stutter.get_time_fraction(), 0.25);
+  }
+
+  #[test]
+  fn should_trigger_with_the_requested_fraction_and_duration() {
+    let mut stutter = Stutter::<1>::new(SAMPLE_RATE);
+    stutter.set_probability(0., 0., 0., 0., 0., 0., 0., 0., 0., 1., 0., 0., 0., 0., 0.);
+
+    let mut trigger_times = Vec::new();
+    for i in 0..6000 {
+      if i == 1000 {
+        stutter.trigger_with(0.25, Some(3.));
+      }
+      let (_, trigger, _) = stutter.process([0.], 0., &SETTINGS);
+      if trigger {
+        let event = stutter.get_last_trigger().unwrap();
+        trigger_times.push((event.time, event.time_fraction, event.duration_multiple));
+      }
+    }
+    // A sixteenth of 300 ms lasts 600 samples and plays three times before auto triggers go on
+    // with random thirds again
+    assert_eq!(
+      trigger_times[..4],
+      [
+        (0, 1. / 3., 1.),
+        (800, 1. / 3., 1.),
+        (1000, 0.25, 3.),
+        (2800, 1. / 3., 1.),
+      ]
+    );
+  }
+
+  #[test]
+  fn should_keep_triggering_when_quantized_slices_last_a_whole_pulse() {
+    let mut stutter = Stutter::<1>::new(SAMPLE_RATE);
+    stutter.set_probability(0., 0., 0., 0., 0., 1., 0., 0., 0., 0., 0., 0., 0., 0., 0.);
+    stutter.set_transient_quantize(100., 0.5);
+
+    // A kick halfway through every beat, while each slice lasts a whole beat as well, so the next
+    // auto trigger arrives while the previous one still waits to be captured
+    let mut trigger_count = 0;
+    for i in 0..80000 {
+      let input = 0.9_f32.powi((i + 500) % 1000);
+      let (_, trigger, _) = stutter.process(
+        [input],
+        0.,
+        &Settings {
+          pulse: 125.,
+          ..SETTINGS
+        },
+      );
+      if trigger {
+        trigger_count += 1;
+      }
+    }
+    assert!(trigger_count >= 30, "triggered {} times", trigger_count);
+  }
+
+  #[test]
+  fn should_start_quantized_slices_on_the_transient() {
+    let mut stutter = Stutter::<1>::new(SAMPLE_RATE);
+    stutter.set_probability(0., 0., 0., 0., 0., 0., 0., 0., 1., 0., 0., 0., 0., 0., 0.);
+    stutter.set_transient_quantize(100., 0.5);
+
+    // A decaying sine that starts well after the manual trigger
+    let input: Vec<f32> = (0..2000)
+      .map(|i| {
+        if i < 300 {
+          0.
+        } else {
+          let time = (i - 300) as f32;
+          (time * TAU * 220. / SAMPLE_RATE).sin() * 0.999_f32.powf(time)
         }
-        _ => (false, false),
-      },
-    )
+      })
+      .collect();
+    let mut output = Vec::new();
+    let mut trigger_times = Vec::new();
+    let mut repeat_times = Vec::new();
+    for (i, x) in input.iter().enumerate() {
+      let ([y], trigger, repeat_trigger) = stutter.process(
+        [*x],
+        0.,
+        &Settings {
+          trigger: i < 10,
+          auto_trigger: false,
+          pulse: 100.,
+          ..SETTINGS
+        },
+      );
+      output.push(y);
+      if trigger {
+        trigger_times.push(i);
+      }
+      if repeat_trigger {
+        repeat_times.push(i);
+      }
+    }
+
+    // The stutter starts on the transient and its first pass is the input itself. The eighth note
+    // of 50 ms is captured just before it starts to repeat.
+    assert_eq!(trigger_times, vec![301]);
+    assert_eq!(repeat_times[..3], [301, 685, 1085]);
+    assert!((461..685).all(|i| (output[i] - input[i]).abs() < 1e-5));
+    assert!((845..1085).all(|i| (output[i] - input[i - 400]).abs() < 1e-5));
+  }
+
+  #[test]
+  fn should_not_click_or_double_trigger_during_tempo_ramps() {
+    for (from, to) in [(60., 180.), (180., 60.)] {
+      for is_stretching in [false, true] {
+        let result = sweep_bpm(from, to, 8., is_stretching);
+
+        // The shortest slice is a sixteenth note at the fastest tempo
+        let shortest_slice = 60000. / 180. * 0.25 * 0.001 * SAMPLE_RATE;
+        assert!(result.trigger_intervals.len() > 16);
+        assert!(result
+          .trigger_intervals
+          .iter()
+          .all(|interval| *interval as f32 >= shortest_slice * 0.9));
+
+        // The sine itself moves up to 0.043 per sample, so anything far above that is a click
+        assert!(result.max_step < 0.1, "max step was {}", result.max_step);
+      }
+    }
   }
 }
@@ -0,0 +1,83 @@
+/// The note values a slice can be, ordered from long to short like `Stutter::set_probability`.
+/// The multipliers are relative to the pulse, which is a quarter note when synced.
+pub const NOTE_VALUES: [(&str, f32); 15] = [
+  ("1/2", 2.),
+  ("7/16", 1.75),
+  ("3/8", 1.5),
+  ("1/2 tripl.", 4. / 3.),
+  ("5/16", 1.25),
+  ("1/4", 1.),
+  ("3/16", 0.75),
+  ("1/4 tripl.", 2. / 3.),
+  ("1/8", 0.5),
+  ("1/8 tripl.", 1. / 3.),
+  ("1/16", 0.25),
+  ("1/16 tripl.", 1. / 6.),
+  ("1/32", 0.125),
+  ("1/32 tripl.", 1. / 12.),
+  ("1/64", 0.0625),
+];
+
+/// Returns the name of the note value that is closest to the given time fraction.
+pub fn get_note_value_name(time_fraction: f32) -> &'static str {
+  NOTE_VALUES
+    .iter()
+    .min_by(|(_, a), (_, b)| {
+      (a - time_fraction)
+        .abs()
+        .total_cmp(&(b - time_fraction).abs())
+    })
+    .map_or("", |(name, _)| name)
+}
+
+/// The MIDI note that triggers a half note. The notes above it trigger the other note values, in
+/// the order of `NOTE_VALUES`.
+pub const FIRST_TRIGGER_NOTE: u8 = 36;
+
+/// Returns the note value a MIDI note triggers, if any.
+pub fn get_midi_note_value(note: u8) -> Option<f32> {
+  let index = note.checked_sub(FIRST_TRIGGER_NOTE)? as usize;
+  NOTE_VALUES
+    .get(index)
+    .map(|(_, time_fraction)| *time_fraction)
+}
+
+/// Returns whether a MIDI channel from 0 to 15 passes the channel setting. A setting of 1 to 16
+/// picks a single channel, while zero accepts every channel.
+pub fn is_note_channel(channel: u8, note_channel: i32) -> bool {
+  note_channel == 0 || channel as i32 + 1 == note_channel
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{
+    get_midi_note_value, get_note_value_name, is_note_channel, FIRST_TRIGGER_NOTE, NOTE_VALUES,
+  };
+
+  #[test]
+  fn should_name_every_note_value() {
+    for (name, time_fraction) in NOTE_VALUES {
+      assert_eq!(get_note_value_name(time_fraction), name);
+    }
+    assert_eq!(get_note_value_name(1.5_f32.recip()), "1/4 tripl.");
+    assert_eq!(get_note_value_name(3.), "1/2");
+  }
+
+  #[test]
+  fn should_map_midi_notes_to_note_values() {
+    assert_eq!(get_midi_note_value(FIRST_TRIGGER_NOTE - 1), None);
+    assert_eq!(get_midi_note_value(FIRST_TRIGGER_NOTE), Some(2.));
+    assert_eq!(get_midi_note_value(FIRST_TRIGGER_NOTE + 10), Some(0.25));
+    assert_eq!(get_midi_note_value(FIRST_TRIGGER_NOTE + 14), Some(0.0625));
+    assert_eq!(get_midi_note_value(FIRST_TRIGGER_NOTE + 15), None);
+  }
+
+  #[test]
+  fn should_filter_midi_channels() {
+    assert!(is_note_channel(0, 0));
+    assert!(is_note_channel(15, 0));
+    assert!(is_note_channel(0, 1));
+    assert!(!is_note_channel(1, 1));
+    assert!(is_note_channel(15, 16));
+  }
+}
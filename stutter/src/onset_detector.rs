@@ -0,0 +1,108 @@
+use crate::shared::float_ext::FloatExt;
+
+pub struct OnsetDetector {
+  fast_envelope: f32,
+  slow_envelope: f32,
+  fast_coefficient: f32,
+  slow_coefficient: f32,
+  threshold: f32,
+  ratio: f32,
+  hold_off: f32,
+  hold_off_counter: usize,
+  sample_rate: f32,
+}
+
+impl OnsetDetector {
+  pub fn new(sample_rate: f32) -> Self {
+    Self {
+      fast_envelope: 0.,
+      slow_envelope: 0.,
+      fast_coefficient: Self::get_coefficient(1., sample_rate),
+      slow_coefficient: Self::get_coefficient(50., sample_rate),
+      threshold: 0.,
+      ratio: 1.,
+      hold_off: 0.,
+      hold_off_counter: 0,
+      sample_rate,
+    }
+  }
+
+  /// Sensitivity ranges from zero to one. A higher sensitivity lowers both the level threshold
+  /// and how far the signal has to rise above its recent average to count as an onset.
+  pub fn set_parameters(&mut self, sensitivity: f32, hold_off: f32) {
+    self.threshold = (-20. - sensitivity * 40.).dbtoa();
+    self.ratio = 1.5 + (1. - sensitivity) * 4.5;
+    self.hold_off = hold_off;
+  }
+
+  pub fn process(&mut self, input: f32) -> bool {
+    let rectified = input.abs();
+    self.fast_envelope += (rectified - self.fast_envelope) * self.fast_coefficient;
+    self.slow_envelope += (rectified - self.slow_envelope) * self.slow_coefficient;
+
+    if self.hold_off_counter > 0 {
+      self.hold_off_counter -= 1;
+      return false;
+    }
+
+    let is_onset =
+      self.fast_envelope > self.threshold && self.fast_envelope > self.slow_envelope * self.ratio;
+    if is_onset {
+      self.hold_off_counter = self.hold_off.mstosamps(self.sample_rate) as usize;
+    }
+    is_onset
+  }
+
+  fn get_coefficient(time: f32, sample_rate: f32) -> f32 {
+    1. - (-1. / time.mstosamps(sample_rate)).exp()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::OnsetDetector;
+
+  fn count_onsets(detector: &mut OnsetDetector, signal: impl Iterator<Item = f32>) -> usize {
+    signal.filter(|x| detector.process(*x)).count()
+  }
+
+  #[test]
+  fn should_detect_a_single_onset_per_hit() {
+    let mut detector = OnsetDetector::new(1000.);
+    detector.set_parameters(0.5, 100.);
+
+    let silence = std::iter::repeat_n(0., 200);
+    let hit = (0..200).map(|i| 0.9_f32.powi(i));
+
+    assert_eq!(count_onsets(&mut detector, silence.clone()), 0);
+    assert_eq!(count_onsets(&mut detector, hit.clone()), 1);
+    assert_eq!(count_onsets(&mut detector, silence), 0);
+    assert_eq!(count_onsets(&mut detector, hit), 1);
+  }
+
+  #[test]
+  fn should_ignore_onsets_during_hold_off() {
+    let mut detector = OnsetDetector::new(1000.);
+    detector.set_parameters(0.5, 100.);
+
+    let hits = (0..100).map(|i| {
+      if i % 40 < 20 {
+        0.9_f32.powi(i % 40)
+      } else {
+        0.
+      }
+    });
+
+    assert_eq!(count_onsets(&mut detector, hits), 1);
+  }
+
+  #[test]
+  fn should_ignore_signals_below_the_threshold() {
+    let mut detector = OnsetDetector::new(1000.);
+    detector.set_parameters(0., 10.);
+
+    let quiet_hit = (0..200).map(|i| 0.01 * 0.9_f32.powi(i));
+
+    assert_eq!(count_onsets(&mut detector, quiet_hit), 0);
+  }
+}
@@ -20,6 +20,8 @@ impl Phasor {
     self.prev_phase = 0.;
   }
 
+  /// The phase is kept when the duration changes, so a pending trigger is retimed in proportion
+  /// to a tempo change instead of restarting its cycle.
   pub fn process(&mut self, duration: f32) -> bool {
     let step_size = duration.mstosamps(self.sample_rate).recip();
     self.phase += step_size;
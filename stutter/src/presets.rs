@@ -0,0 +1,222 @@
+// The factory presets are defined once here and shared by every plugin format. Their values are
+// plain parameter values, keyed by the parameter ids of the plugins, which double as the symbols
+// of the LV2 ports. Performance controls like on, trigger and tap are left out on purpose, just
+// like the routing and MIDI settings, so loading a preset never starts or stops the stutter.
+
+use crate::auto_pan::PanPattern;
+
+/// The parameter ids of the note probabilities, ordered from long to short like `set_probability`.
+pub const PROBABILITY_IDS: [&str; 15] = [
+  "half_notes",
+  "seven_sixteenth_notes",
+  "six_sixteenth_notes",
+  "half_triplet_notes",
+  "five_sixteenth_notes",
+  "quarter_notes",
+  "three_sixteenth_notes",
+  "quarter_triplet_notes",
+  "eighth_notes",
+  "eighth_triplet_notes",
+  "sixteenth_notes",
+  "sixteenth_triplet_notes",
+  "thirty_second_notes",
+  "thirty_second_triplet_notes",
+  "sixty_fourth_notes",
+];
+
+pub struct Preset {
+  pub name: &'static str,
+  pub auto: bool,
+  pub sync: bool,
+  pub mix: i32,
+  pub pulse: f32,
+  pub tempo_factor: usize,
+  pub sync_mode: i32,
+  pub free_ratio: bool,
+  pub ratio: f32,
+  pub stretch: bool,
+  pub chance: f32,
+  pub duration: f32,
+  pub pan_pattern: PanPattern,
+  pub pan_amount: f32,
+  pub width: f32,
+  pub quantize: f32,
+  pub triplet_balance: f32,
+  pub odd_balance: f32,
+  pub tilt: f32,
+  pub probability: [f32; 15],
+}
+
+impl Preset {
+  /// Returns the parameter ids with the plain values of this preset.
+  pub fn get_values(&self) -> Vec<(&'static str, f32)> {
+    let mut values = vec![
+      ("auto", Self::from_bool(self.auto)),
+      ("sync", Self::from_bool(self.sync)),
+      ("mix", self.mix as f32),
+      ("pulse", self.pulse),
+      ("tempo_factor", self.tempo_factor as f32),
+      ("sync_mode", self.sync_mode as f32),
+      ("free_ratio", Self::from_bool(self.free_ratio)),
+      ("ratio", self.ratio),
+      ("stretch", Self::from_bool(self.stretch)),
+      ("chance", self.chance),
+      ("duration", self.duration),
+      ("pan_pattern", self.pan_pattern as i32 as f32),
+      ("pan_amount", self.pan_amount),
+      ("width", self.width),
+      ("quantize", self.quantize),
+      ("triplet_balance", self.triplet_balance),
+      ("odd_balance", self.odd_balance),
+      ("tilt", self.tilt),
+    ];
+    values.extend(PROBABILITY_IDS.into_iter().zip(self.probability));
+    values
+  }
+
+  fn from_bool(value: bool) -> f32 {
+    if value {
+      1.
+    } else {
+      0.
+    }
+  }
+}
+
+const INIT: Preset = Preset {
+  name: "Init",
+  auto: true,
+  sync: true,
+  mix: 0,
+  pulse: 500.,
+  tempo_factor: 2,
+  sync_mode: 0,
+  free_ratio: false,
+  ratio: 1.,
+  stretch: false,
+  chance: 0.5,
+  duration: 0.5,
+  pan_pattern: PanPattern::Off,
+  pan_amount: 1.,
+  width: 1.,
+  quantize: 0.,
+  triplet_balance: 0.,
+  odd_balance: 0.,
+  tilt: 0.,
+  probability: [
+    0., 0., 0.25, 0., 0., 0.25, 0., 0., 0.25, 0., 0., 0., 0.25, 0., 0.,
+  ],
+};
+
+pub const FACTORY_PRESETS: [Preset; 6] = [
+  INIT,
+  Preset {
+    name: "Trap hat rolls",
+    chance: 0.6,
+    duration: 0.25,
+    pan_pattern: PanPattern::Alternate,
+    pan_amount: 0.4,
+    probability: [
+      0., 0., 0., 0., 0., 0., 0., 0., 0., 0., 0.5, 0.6, 1., 0.8, 0.5,
+    ],
+    ..INIT
+  },
+  Preset {
+    name: "Tape glitch",
+    stretch: true,
+    chance: 0.4,
+    duration: 0.6,
+    pan_pattern: PanPattern::Random,
+    pan_amount: 0.3,
+    quantize: 20.,
+    probability: [
+      0., 0., 0., 0., 0.3, 0., 0.3, 0.5, 0.6, 0.4, 0., 0., 0., 0., 0.,
+    ],
+    ..INIT
+  },
+  Preset {
+    name: "Half-time breakdown",
+    tempo_factor: 3,
+    chance: 0.8,
+    duration: 1.,
+    width: 0.6,
+    probability: [0.5, 0., 0.4, 0., 0., 1., 0., 0., 0., 0., 0., 0., 0., 0., 0.],
+    ..INIT
+  },
+  Preset {
+    name: "Odd meter stutter",
+    chance: 0.5,
+    duration: 0.4,
+    pan_pattern: PanPattern::Sweep,
+    pan_amount: 0.7,
+    probability: [
+      0., 0.5, 0., 0., 0.6, 0., 0.8, 0., 0., 0., 0., 0., 0., 0., 0.,
+    ],
+    ..INIT
+  },
+  Preset {
+    name: "Triplet swing",
+    sync_mode: 1,
+    mix: 1,
+    chance: 0.7,
+    duration: 0.3,
+    probability: [
+      0., 0., 0., 0.2, 0., 0., 0., 0.6, 0., 1., 0., 0.5, 0., 0., 0.,
+    ],
+    ..INIT
+  },
+];
+
+#[cfg(test)]
+mod tests {
+  use super::{FACTORY_PRESETS, PROBABILITY_IDS};
+  use crate::tempo_factor::TEMPO_FACTORS;
+
+  #[test]
+  fn should_have_unique_preset_names() {
+    for (index, preset) in FACTORY_PRESETS.iter().enumerate() {
+      assert!(FACTORY_PRESETS[index + 1..]
+        .iter()
+        .all(|other| other.name != preset.name));
+    }
+  }
+
+  #[test]
+  fn should_keep_preset_values_in_range() {
+    for preset in FACTORY_PRESETS {
+      assert!((0..3).contains(&preset.mix), "{}", preset.name);
+      assert!((0..3).contains(&preset.sync_mode), "{}", preset.name);
+      assert!(preset.tempo_factor < TEMPO_FACTORS.len(), "{}", preset.name);
+      assert!((10. ..=3000.).contains(&preset.pulse), "{}", preset.name);
+      assert!((0.25..=4.).contains(&preset.ratio), "{}", preset.name);
+      assert!((0. ..=500.).contains(&preset.quantize), "{}", preset.name);
+      assert!((0. ..=2.).contains(&preset.width), "{}", preset.name);
+      for value in [preset.triplet_balance, preset.odd_balance, preset.tilt] {
+        assert!((-1. ..=1.).contains(&value), "{}", preset.name);
+      }
+      for value in [preset.chance, preset.duration, preset.pan_amount]
+        .iter()
+        .chain(&preset.probability)
+      {
+        assert!((0. ..=1.).contains(value), "{}", preset.name);
+      }
+      assert!(
+        preset
+          .probability
+          .iter()
+          .any(|probability| *probability > 0.),
+        "{} has no note probability",
+        preset.name
+      );
+    }
+  }
+
+  #[test]
+  fn should_return_every_value_once() {
+    let values = FACTORY_PRESETS[0].get_values();
+    assert_eq!(values.len(), 18 + PROBABILITY_IDS.len());
+    for (index, (id, _)) in values.iter().enumerate() {
+      assert!(values[index + 1..].iter().all(|(other, _)| other != id));
+    }
+  }
+}
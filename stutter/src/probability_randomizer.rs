@@ -0,0 +1,111 @@
+// Rewrites the fifteen note probabilities at once. The constraint limits which notes can get a
+// probability: 0 allows every note, 1 only straight notes, 2 only triplets and 3 only notes of an
+// eighth or shorter. Notes outside of the constraint are set to zero.
+
+const STRAIGHT_NOTES: [usize; 6] = [0, 5, 8, 10, 12, 14];
+const TRIPLET_NOTES: [usize; 5] = [3, 7, 9, 11, 13];
+const FIRST_SHORT_NOTE: usize = 8;
+// The chance that a note is left out when randomizing, so the result doesn't use every note.
+const SPARSENESS: f32 = 0.5;
+
+/// Returns a new set of random note probabilities.
+pub fn randomize_probability(constraint: i32) -> [f32; 15] {
+  let probability = std::array::from_fn(|index| {
+    if is_allowed(index, constraint) && fastrand::f32() >= SPARSENESS {
+      round(fastrand::f32())
+    } else {
+      0.
+    }
+  });
+  ensure_a_note(probability, constraint)
+}
+
+/// Moves every note probability randomly by up to `amount`, where 1 equals 100%.
+pub fn mutate_probability(probability: [f32; 15], amount: f32, constraint: i32) -> [f32; 15] {
+  let probability = std::array::from_fn(|index| {
+    if is_allowed(index, constraint) {
+      round(probability[index] + (fastrand::f32() * 2. - 1.) * amount).clamp(0., 1.)
+    } else {
+      0.
+    }
+  });
+  ensure_a_note(probability, constraint)
+}
+
+fn is_allowed(index: usize, constraint: i32) -> bool {
+  match constraint {
+    1 => STRAIGHT_NOTES.contains(&index),
+    2 => TRIPLET_NOTES.contains(&index),
+    3 => index >= FIRST_SHORT_NOTE,
+    _ => true,
+  }
+}
+
+// Without any probability the stutter falls back to every note, so at least one note is kept.
+fn ensure_a_note(mut probability: [f32; 15], constraint: i32) -> [f32; 15] {
+  if probability.iter().all(|value| *value == 0.) {
+    let allowed_notes = (0..probability.len())
+      .filter(|index| is_allowed(*index, constraint))
+      .collect::<Vec<_>>();
+    probability[allowed_notes[fastrand::usize(..allowed_notes.len())]] = 1.;
+  }
+  probability
+}
+
+fn round(value: f32) -> f32 {
+  (value * 100.).round() / 100.
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{
+    is_allowed, mutate_probability, randomize_probability, STRAIGHT_NOTES, TRIPLET_NOTES,
+  };
+
+  #[test]
+  fn should_only_randomize_allowed_notes() {
+    fastrand::seed(1);
+    for constraint in 0..4 {
+      for _ in 0..100 {
+        let probability = randomize_probability(constraint);
+        assert!(probability.iter().any(|value| *value > 0.));
+        for (index, value) in probability.iter().enumerate() {
+          assert!((0. ..=1.).contains(value));
+          if !is_allowed(index, constraint) {
+            assert_eq!(*value, 0.);
+          }
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn should_apply_constraints() {
+    let allowed = |constraint| (0..15).filter(move |index| is_allowed(*index, constraint));
+    assert_eq!(allowed(0).count(), 15);
+    assert!(allowed(1).eq(STRAIGHT_NOTES));
+    assert!(allowed(2).eq(TRIPLET_NOTES));
+    assert!(allowed(3).eq(8..15));
+  }
+
+  #[test]
+  fn should_mutate_within_the_amount() {
+    fastrand::seed(2);
+    let probability = [0.5; 15];
+    for _ in 0..100 {
+      let mutated = mutate_probability(probability, 0.1, 0);
+      assert!(mutated
+        .iter()
+        .all(|value| (*value - 0.5).abs() <= 0.1 + f32::EPSILON));
+    }
+    assert_eq!(mutate_probability(probability, 0., 0), probability);
+  }
+
+  #[test]
+  fn should_keep_a_note_when_mutating_to_zero() {
+    fastrand::seed(3);
+    let mutated = mutate_probability([0.; 15], 0., 2);
+    assert_eq!(mutated.iter().filter(|value| **value == 1.).count(), 1);
+    assert!(TRIPLET_NOTES.iter().any(|index| mutated[*index] == 1.));
+  }
+}
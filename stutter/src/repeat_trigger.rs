@@ -15,16 +15,18 @@ impl RepeatTrigger {
     }
   }
 
-  pub fn process(
+  pub fn process<const N: usize>(
     &mut self,
-    delay: &[Delay; 2],
+    delay: &[Delay<N>; 2],
     trigger: bool,
     flip_flop: bool,
-    delay_time: f32,
   ) -> bool {
-    let active_delay_index = if flip_flop { 0 } else { 1 };
-    let write_index = delay[active_delay_index].get_write_index();
+    let active_delay = &delay[if flip_flop { 0 } else { 1 }];
+    let write_index = active_delay.get_write_index();
     if trigger || write_index == self.next_write_index_trigger as usize {
+      // Use the delay time of the slice itself, because the pulse may have changed since the
+      // slice was captured.
+      let delay_time = active_delay.get_delay_time();
       self.next_write_index_trigger = self.wrap(write_index as f32 + self.mstosamps(delay_time));
       return true;
     }
@@ -0,0 +1,17 @@
+/// The controls the stutter reads on every sample. Plugins usually fill this in once per block,
+/// but each field can also be changed per sample, for example to follow a CV input.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Settings {
+  /// Whether the stutter can be heard. Switching it on triggers as well.
+  pub on: bool,
+  /// The manual trigger. It triggers on every change, or only when it rises while it's momentary.
+  pub trigger: bool,
+  /// Whether the stutter keeps triggering by itself after each duration.
+  pub auto_trigger: bool,
+  pub mix: i32,
+  /// The length of a quarter note in milliseconds.
+  pub pulse: f32,
+  pub duration: f32,
+  pub chance: f32,
+  pub is_momentary_trigger: bool,
+}
@@ -1,14 +1,16 @@
-pub trait TupleExt {
+use std::array;
+
+pub trait ArrayExt {
   fn add(self, other: Self) -> Self;
   fn multiply_with(self, factor: f32) -> Self;
 }
 
-impl TupleExt for (f32, f32) {
+impl<const N: usize> ArrayExt for [f32; N] {
   fn add(self, other: Self) -> Self {
-    (self.0 + other.0, self.1 + other.1)
+    array::from_fn(|i| self[i] + other[i])
   }
 
   fn multiply_with(self, factor: f32) -> Self {
-    (self.0 * factor, self.1 * factor)
+    self.map(|x| x * factor)
   }
 }
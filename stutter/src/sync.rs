@@ -0,0 +1,79 @@
+// Hosts report the tempo in quarter notes per minute, which doesn't line up with the beat in
+// meters like 6/8 or 7/8. The sync mode sets which note value the synced pulse is based on:
+// 0 is a quarter note, 1 is the beat of the time signature, where compound meters like 6/8 count
+// in dotted notes, and 2 is a quarter of a bar, so a half note becomes half a bar in any meter.
+
+/// Returns the pulse time in milliseconds for a tempo in quarter notes per minute.
+pub fn get_synced_pulse_time(bpm: f32, numerator: f32, denominator: f32, sync_mode: i32) -> f32 {
+  let bpm = if bpm.is_finite() && bpm > 0. {
+    bpm
+  } else {
+    120.
+  };
+  60000. / bpm * get_pulse_length(numerator, denominator, sync_mode)
+}
+
+/// Returns the length of the pulse in quarter notes.
+pub fn get_pulse_length(numerator: f32, denominator: f32, sync_mode: i32) -> f32 {
+  let (numerator, denominator) = if is_valid(numerator) && is_valid(denominator) {
+    (numerator.round(), denominator.round())
+  } else {
+    (4., 4.)
+  };
+  let beat_unit = 4. / denominator;
+
+  match sync_mode {
+    1 => {
+      if is_compound(numerator, denominator) {
+        beat_unit * 3.
+      } else {
+        beat_unit
+      }
+    }
+    2 => numerator * beat_unit / 4.,
+    _ => 1.,
+  }
+}
+
+fn is_valid(value: f32) -> bool {
+  value.is_finite() && value >= 1.
+}
+
+fn is_compound(numerator: f32, denominator: f32) -> bool {
+  denominator >= 8. && numerator > 3. && numerator % 3. == 0.
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{get_pulse_length, get_synced_pulse_time};
+
+  #[test]
+  fn should_sync_to_quarter_notes_in_any_meter() {
+    assert_eq!(get_synced_pulse_time(120., 4., 4., 0), 500.);
+    assert_eq!(get_synced_pulse_time(120., 7., 8., 0), 500.);
+  }
+
+  #[test]
+  fn should_sync_to_the_beat_of_the_time_signature() {
+    assert_eq!(get_pulse_length(4., 4., 1), 1.);
+    assert_eq!(get_pulse_length(7., 8., 1), 0.5);
+    assert_eq!(get_pulse_length(6., 8., 1), 1.5);
+    assert_eq!(get_pulse_length(3., 8., 1), 0.5);
+    assert_eq!(get_pulse_length(2., 2., 1), 2.);
+  }
+
+  #[test]
+  fn should_sync_to_a_quarter_of_a_bar() {
+    assert_eq!(get_pulse_length(4., 4., 2), 1.);
+    assert_eq!(get_pulse_length(3., 4., 2), 0.75);
+    assert_eq!(get_pulse_length(7., 8., 2), 0.875);
+    assert_eq!(get_synced_pulse_time(120., 6., 8., 2), 375.);
+  }
+
+  #[test]
+  fn should_fall_back_to_common_time_and_120_bpm() {
+    assert_eq!(get_pulse_length(0., 4., 2), 1.);
+    assert_eq!(get_pulse_length(4., f32::NAN, 1), 1.);
+    assert_eq!(get_synced_pulse_time(0., 4., 4., 0), 500.);
+  }
+}
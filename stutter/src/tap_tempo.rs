@@ -0,0 +1,176 @@
+use crate::manual_trigger::ManualTrigger;
+
+const MAX_INTERVALS: usize = 4;
+// Taps that are further apart than this start a new tempo.
+const TIMEOUT: f32 = 2000.;
+// Intervals that deviate more than this from the average are ignored as mistimed taps.
+const OUTLIER_THRESHOLD: f32 = 0.4;
+
+/// Averages the time between taps into a pulse time. Taps are read once per block, so the block
+/// length has to be passed to `advance`. A single mistimed tap is ignored, but two outliers in a
+/// row that agree with each other start a new tempo.
+pub struct TapTempo {
+  manual_trigger: ManualTrigger,
+  intervals: [f32; MAX_INTERVALS],
+  interval_count: usize,
+  interval_index: usize,
+  outlier: Option<f32>,
+  samples_since_tap: Option<u64>,
+  sample_rate: f32,
+}
+
+impl TapTempo {
+  pub fn new(sample_rate: f32) -> Self {
+    Self {
+      manual_trigger: ManualTrigger::new(),
+      intervals: [0.; MAX_INTERVALS],
+      interval_count: 0,
+      interval_index: 0,
+      outlier: None,
+      samples_since_tap: None,
+      sample_rate,
+    }
+  }
+
+  /// Returns true when a tap was registered.
+  pub fn process(&mut self, tap: bool, is_momentary_trigger: bool) -> bool {
+    if !self.manual_trigger.process(tap, is_momentary_trigger) {
+      return false;
+    }
+
+    match self.samples_since_tap {
+      Some(samples) => {
+        let interval = samples as f32 / self.sample_rate * 1000.;
+        if interval > TIMEOUT {
+          self.reset();
+        } else if !self.is_outlier(interval) {
+          self.outlier = None;
+          self.add_interval(interval);
+        } else {
+          match self.outlier.take() {
+            Some(outlier) if !deviates(interval, outlier) => {
+              self.reset();
+              self.add_interval(outlier);
+              self.add_interval(interval);
+            }
+            _ => self.outlier = Some(interval),
+          }
+        }
+      }
+      None => self.reset(),
+    }
+    self.samples_since_tap = Some(0);
+
+    true
+  }
+
+  pub fn advance(&mut self, samples: u32) {
+    if let Some(samples_since_tap) = self.samples_since_tap.as_mut() {
+      *samples_since_tap += samples as u64;
+    }
+  }
+
+  /// Forgets the tapped tempo, for example when the pulse knob takes over again.
+  pub fn reset(&mut self) {
+    self.interval_count = 0;
+    self.interval_index = 0;
+    self.outlier = None;
+  }
+
+  /// Returns the averaged time between taps in milliseconds, or None until two taps arrived.
+  pub fn get_pulse_time(&self) -> Option<f32> {
+    if self.interval_count == 0 {
+      None
+    } else {
+      Some(self.get_average())
+    }
+  }
+
+  fn add_interval(&mut self, interval: f32) {
+    self.intervals[self.interval_index] = interval;
+    self.interval_index = (self.interval_index + 1) % MAX_INTERVALS;
+    self.interval_count = (self.interval_count + 1).min(MAX_INTERVALS);
+  }
+
+  fn get_average(&self) -> f32 {
+    self.intervals[..self.interval_count].iter().sum::<f32>() / self.interval_count as f32
+  }
+
+  fn is_outlier(&self, interval: f32) -> bool {
+    if self.interval_count < 2 {
+      return false;
+    }
+    deviates(interval, self.get_average())
+  }
+}
+
+fn deviates(interval: f32, reference: f32) -> bool {
+  ((interval - reference) / reference).abs() > OUTLIER_THRESHOLD
+}
+
+#[cfg(test)]
+mod tests {
+  use super::TapTempo;
+
+  fn tap(tap_tempo: &mut TapTempo, intervals: &[u32]) {
+    for interval in intervals {
+      tap_tempo.process(true, true);
+      tap_tempo.process(false, true);
+      tap_tempo.advance(*interval);
+    }
+  }
+
+  #[test]
+  fn should_average_the_time_between_taps() {
+    let mut tap_tempo = TapTempo::new(1000.);
+    assert_eq!(tap_tempo.get_pulse_time(), None);
+
+    tap(&mut tap_tempo, &[490, 510, 500, 0]);
+    assert_eq!(tap_tempo.get_pulse_time(), Some(500.));
+  }
+
+  #[test]
+  fn should_ignore_outliers() {
+    let mut tap_tempo = TapTempo::new(1000.);
+
+    tap(&mut tap_tempo, &[500, 500, 100, 500, 0]);
+    assert_eq!(tap_tempo.get_pulse_time(), Some(500.));
+  }
+
+  #[test]
+  fn should_follow_a_new_tempo_after_two_consistent_outliers() {
+    let mut tap_tempo = TapTempo::new(1000.);
+
+    tap(&mut tap_tempo, &[500, 500, 500, 250, 250]);
+    assert_eq!(tap_tempo.get_pulse_time(), Some(500.));
+
+    tap(&mut tap_tempo, &[0]);
+    assert_eq!(tap_tempo.get_pulse_time(), Some(250.));
+  }
+
+  #[test]
+  fn should_not_follow_outliers_that_disagree() {
+    let mut tap_tempo = TapTempo::new(1000.);
+
+    tap(&mut tap_tempo, &[500, 500, 500, 100, 900, 0]);
+    assert_eq!(tap_tempo.get_pulse_time(), Some(500.));
+  }
+
+  #[test]
+  fn should_start_over_after_a_timeout() {
+    let mut tap_tempo = TapTempo::new(1000.);
+
+    tap(&mut tap_tempo, &[500, 500, 3000, 800, 0]);
+    assert_eq!(tap_tempo.get_pulse_time(), Some(800.));
+  }
+
+  #[test]
+  fn should_register_every_change_of_a_toggling_trigger() {
+    let mut tap_tempo = TapTempo::new(1000.);
+
+    assert!(tap_tempo.process(true, false));
+    tap_tempo.advance(400);
+    assert!(tap_tempo.process(false, false));
+    assert_eq!(tap_tempo.get_pulse_time(), Some(400.));
+  }
+}
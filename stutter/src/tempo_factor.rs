@@ -0,0 +1,173 @@
+/// The tempo factors that the synced pulse can be multiplied with. Sessions, presets and
+/// automation store the index, so the straight factors keep their original indices and the triplet
+/// and dotted factors are appended after them. Only add new factors at the end.
+pub const TEMPO_FACTORS: [(&str, f32); 11] = [
+  ("0.25", 0.25),
+  ("0.5", 0.5),
+  ("1", 1.),
+  ("2", 2.),
+  ("4", 4.),
+  ("1/3", 1. / 3.),
+  ("2/3", 2. / 3.),
+  ("0.75", 0.75),
+  ("4/3", 4. / 3.),
+  ("1.5", 1.5),
+  ("3", 3.),
+];
+/// The indices of `TEMPO_FACTORS` from the smallest to the largest factor, so controls can show
+/// them in order while the stored index stays the same.
+pub const SORTED_TEMPO_FACTORS: [usize; 11] = [0, 5, 1, 6, 7, 2, 8, 9, 3, 10, 4];
+pub const DEFAULT_TEMPO_FACTOR: usize = 2;
+pub const MIN_RATIO: f32 = 0.25;
+pub const MAX_RATIO: f32 = 4.;
+
+/// Maps the index of a tempo factor to its multiplier. Hosts can send interpolated or out of
+/// range values, so the index is rounded and clamped to the nearest tempo factor.
+pub fn map_tempo_factor(index: f32) -> f32 {
+  TEMPO_FACTORS[clamp_index(index)].1
+}
+
+pub fn get_tempo_factor_name(index: f32) -> &'static str {
+  TEMPO_FACTORS[clamp_index(index)].0
+}
+
+/// Returns the index of the tempo factor that is closest to the given multiplier.
+pub fn find_tempo_factor(multiplier: f32) -> usize {
+  TEMPO_FACTORS
+    .iter()
+    .enumerate()
+    .min_by(|(_, (_, a)), (_, (_, b))| (a - multiplier).abs().total_cmp(&(b - multiplier).abs()))
+    .map(|(index, _)| index)
+    .unwrap_or(DEFAULT_TEMPO_FACTOR)
+}
+
+/// Parses a tempo factor typed in by the user, like "1.5", "3x" or "1/3 x", and returns the index
+/// of the closest tempo factor. Returns None when the text isn't a positive number.
+pub fn parse_tempo_factor(string: &str) -> Option<usize> {
+  let string = string.trim().trim_end_matches(['x', 'X', '×']).trim_end();
+  let multiplier = match string.split_once('/') {
+    Some((numerator, denominator)) => {
+      numerator.trim().parse::<f32>().ok()? / denominator.trim().parse::<f32>().ok()?
+    }
+    None => string.parse::<f32>().ok()?,
+  };
+
+  if multiplier.is_finite() && multiplier > 0. {
+    Some(find_tempo_factor(multiplier))
+  } else {
+    None
+  }
+}
+
+/// Clamps a free tempo ratio to the same range as the tempo factors.
+pub fn clamp_ratio(ratio: f32) -> f32 {
+  if ratio.is_nan() {
+    1.
+  } else {
+    ratio.clamp(MIN_RATIO, MAX_RATIO)
+  }
+}
+
+fn clamp_index(index: f32) -> usize {
+  if index.is_nan() {
+    DEFAULT_TEMPO_FACTOR
+  } else {
+    (index.round().max(0.) as usize).min(TEMPO_FACTORS.len() - 1)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{
+    clamp_ratio, find_tempo_factor, get_tempo_factor_name, map_tempo_factor, parse_tempo_factor,
+    DEFAULT_TEMPO_FACTOR, SORTED_TEMPO_FACTORS, TEMPO_FACTORS,
+  };
+
+  #[test]
+  fn should_map_every_index_to_a_tempo_factor() {
+    for (index, (_, multiplier)) in TEMPO_FACTORS.iter().enumerate() {
+      assert_eq!(map_tempo_factor(index as f32), *multiplier);
+    }
+  }
+
+  #[test]
+  fn should_keep_the_indices_of_earlier_versions() {
+    for (index, multiplier) in [0.25, 0.5, 1., 2., 4.].iter().enumerate() {
+      assert_eq!(map_tempo_factor(index as f32), *multiplier);
+    }
+    assert_eq!(map_tempo_factor(DEFAULT_TEMPO_FACTOR as f32), 1.);
+  }
+
+  #[test]
+  fn should_sort_every_tempo_factor() {
+    let mut indices = SORTED_TEMPO_FACTORS.to_vec();
+    indices.sort();
+    assert_eq!(indices, (0..TEMPO_FACTORS.len()).collect::<Vec<_>>());
+    assert!(SORTED_TEMPO_FACTORS
+      .windows(2)
+      .all(|pair| TEMPO_FACTORS[pair[0]].1 < TEMPO_FACTORS[pair[1]].1));
+  }
+
+  #[test]
+  fn should_clamp_unexpected_indices() {
+    assert_eq!(map_tempo_factor(-1.), 0.25);
+    assert_eq!(map_tempo_factor(-0.4), 0.25);
+    assert_eq!(map_tempo_factor(1.4), 0.5);
+    assert_eq!(map_tempo_factor(1.5), 1.);
+    assert_eq!(map_tempo_factor(10.4), 3.);
+    assert_eq!(map_tempo_factor(100.), 3.);
+    assert_eq!(map_tempo_factor(f32::INFINITY), 3.);
+    assert_eq!(map_tempo_factor(f32::NEG_INFINITY), 0.25);
+    assert_eq!(map_tempo_factor(f32::NAN), 1.);
+    assert_eq!(get_tempo_factor_name(f32::NAN), "1");
+  }
+
+  #[test]
+  fn should_find_the_nearest_tempo_factor() {
+    assert_eq!(map_tempo_factor(find_tempo_factor(0.33) as f32), 1. / 3.);
+    assert_eq!(map_tempo_factor(find_tempo_factor(1.45) as f32), 1.5);
+    assert_eq!(map_tempo_factor(find_tempo_factor(8.) as f32), 4.);
+    assert_eq!(map_tempo_factor(find_tempo_factor(0.) as f32), 0.25);
+  }
+
+  #[test]
+  fn should_parse_every_displayed_tempo_factor() {
+    for (index, (name, _)) in TEMPO_FACTORS.iter().enumerate() {
+      assert_eq!(parse_tempo_factor(name), Some(index));
+      assert_eq!(parse_tempo_factor(&format!("{name} x")), Some(index));
+      assert_eq!(parse_tempo_factor(&format!("{name}x")), Some(index));
+      assert_eq!(parse_tempo_factor(&format!(" {name} X ")), Some(index));
+    }
+  }
+
+  #[test]
+  fn should_parse_user_input_to_the_nearest_tempo_factor() {
+    assert_eq!(parse_tempo_factor("3x"), Some(10));
+    assert_eq!(parse_tempo_factor("0.333"), Some(5));
+    assert_eq!(parse_tempo_factor("1 / 3"), Some(5));
+    assert_eq!(parse_tempo_factor("3/2 ×"), Some(9));
+    assert_eq!(parse_tempo_factor("8"), Some(4));
+    assert_eq!(parse_tempo_factor("0.01"), Some(0));
+  }
+
+  #[test]
+  fn should_reject_invalid_user_input() {
+    assert_eq!(parse_tempo_factor(""), None);
+    assert_eq!(parse_tempo_factor("x"), None);
+    assert_eq!(parse_tempo_factor("fast"), None);
+    assert_eq!(parse_tempo_factor("-2"), None);
+    assert_eq!(parse_tempo_factor("0"), None);
+    assert_eq!(parse_tempo_factor("1/0"), None);
+    assert_eq!(parse_tempo_factor("1/"), None);
+    assert_eq!(parse_tempo_factor("NaN"), None);
+    assert_eq!(parse_tempo_factor("inf"), None);
+  }
+
+  #[test]
+  fn should_clamp_free_ratios() {
+    assert_eq!(clamp_ratio(0.1), 0.25);
+    assert_eq!(clamp_ratio(1.5), 1.5);
+    assert_eq!(clamp_ratio(10.), 4.);
+    assert_eq!(clamp_ratio(f32::NAN), 1.);
+  }
+}
@@ -1,17 +1,55 @@
+// The exponent of the tilt at its extremes. A full tilt weighs a half note 4 times heavier and a
+// sixteenth note 256 times lighter, or the other way around.
+const TILT_RANGE: f32 = 2.;
+
 pub struct TimeFractionGenerator {
   fraction: f32,
+  forced_fraction: Option<f32>,
   probability: Vec<(f32, f32)>,
+  triplet_balance: f32,
+  odd_balance: f32,
+  tilt: f32,
+}
+
+impl Default for TimeFractionGenerator {
+  fn default() -> Self {
+    Self::new()
+  }
 }
 
 impl TimeFractionGenerator {
   pub fn new() -> Self {
     Self {
       fraction: 1.,
+      forced_fraction: None,
       probability: Vec::with_capacity(15),
+      triplet_balance: 0.,
+      odd_balance: 0.,
+      tilt: 0.,
     }
   }
 
+  /// Biases the note probabilities without changing them. The balances weigh triplets and odd
+  /// notes against straight notes, from -1 to leave them out to 1 to make them four times as
+  /// likely. The tilt goes from -1 to favour short notes to 1 to favour long notes. Zero leaves
+  /// the probabilities as they are. This takes effect on the next call to `set_probability`.
+  pub fn set_bias(&mut self, triplet_balance: f32, odd_balance: f32, tilt: f32) {
+    self.triplet_balance = triplet_balance.clamp(-1., 1.);
+    self.odd_balance = odd_balance.clamp(-1., 1.);
+    self.tilt = tilt.clamp(-1., 1.);
+  }
+
   pub fn set_probability(&mut self, probability: [(f32, f32); 15]) {
+    // When the bias leaves out every note that has a probability, it's ignored instead of
+    // falling back to an even spread over all notes.
+    let biased_probability =
+      probability.map(|(chance, value)| (chance * self.get_bias(value), value));
+    let probability = if biased_probability.iter().any(|(chance, _)| *chance > 0.) {
+      biased_probability
+    } else {
+      probability
+    };
+
     let accumulated_probability = probability
       .into_iter()
       .scan((0., 0.), |acc, (chance, value)| {
@@ -38,15 +76,60 @@ impl TimeFractionGenerator {
     }
   }
 
-  pub fn process(&mut self, trigger: bool) -> f32 {
+  /// Returns the chance of every note after normalizing and biasing the probabilities, in the
+  /// order they were set.
+  pub fn get_distribution(&self) -> [f32; 15] {
+    let mut distribution = [0.; 15];
+    let mut previous = 0.;
+    for (chance, (accumulated, _)) in distribution.iter_mut().zip(&self.probability) {
+      *chance = accumulated - previous;
+      previous = *accumulated;
+    }
+    distribution
+  }
+
+  /// Makes the next manual trigger use this fraction instead of a random one. Auto triggers keep
+  /// picking random fractions in the meantime.
+  pub fn force_fraction(&mut self, fraction: f32) {
+    self.forced_fraction = Some(fraction);
+  }
+
+  pub fn process(&mut self, trigger: bool, is_manual_trigger: bool) -> f32 {
     if trigger {
-      let random_num = fastrand::f32();
-      self.fraction = self.get_fraction(random_num);
+      self.fraction = match self.forced_fraction {
+        Some(fraction) if is_manual_trigger => {
+          self.forced_fraction = None;
+          fraction
+        }
+        _ => self.get_fraction(fastrand::f32()),
+      };
     }
 
     self.fraction
   }
 
+  fn get_bias(&self, fraction: f32) -> f32 {
+    let balance = if Self::is_power_of_two(fraction) {
+      0.
+    } else if Self::is_power_of_two(fraction * 3.) {
+      self.triplet_balance
+    } else {
+      self.odd_balance
+    };
+    let balance_gain = if balance < 0. {
+      1. + balance
+    } else {
+      1. + balance * 3.
+    };
+
+    balance_gain * fraction.powf(self.tilt * TILT_RANGE)
+  }
+
+  fn is_power_of_two(value: f32) -> bool {
+    let exponent = value.log2();
+    (exponent - exponent.round()).abs() < 0.001
+  }
+
   fn get_fraction(&self, random_num: f32) -> f32 {
     match self.probability.iter().find(|item| random_num < item.0) {
       Some((_, fraction)) => *fraction,
@@ -193,4 +276,114 @@ mod tests {
     assert_eq!(fraction_gen.get_fraction(0.4), 1.5);
     assert_eq!(fraction_gen.get_fraction(0.7), 1.3333334);
   }
+
+  fn get_probability_table() -> [(f32, f32); 15] {
+    [
+      (1., 2.),
+      (0., 1.75),
+      (1., 1.5),
+      (1., 1.33333333),
+      (0., 1.25),
+      (1., 1.),
+      (0., 0.75),
+      (1., 1.5_f32.recip()),
+      (1., 2_f32.recip()),
+      (0., 3_f32.recip()),
+      (1., 4_f32.recip()),
+      (0., 6_f32.recip()),
+      (0., 8_f32.recip()),
+      (0., 12_f32.recip()),
+      (0., 16_f32.recip()),
+    ]
+  }
+
+  fn get_chance(fraction_gen: &TimeFractionGenerator, fraction: f32) -> f32 {
+    let index = fraction_gen
+      .probability
+      .iter()
+      .position(|(_, value)| *value == fraction)
+      .unwrap();
+    (fraction_gen.get_distribution()[index] * 10000.).round() / 10000.
+  }
+
+  #[test]
+  fn should_balance_note_groups() {
+    let fraction_gen = &mut TimeFractionGenerator::new();
+
+    fraction_gen.set_bias(-1., 0., 0.);
+    fraction_gen.set_probability(get_probability_table());
+    assert_eq!(get_chance(fraction_gen, 1.33333333), 0.);
+    assert_eq!(get_chance(fraction_gen, 1.5_f32.recip()), 0.);
+    assert_eq!(get_chance(fraction_gen, 1.), 0.2);
+
+    fraction_gen.set_bias(0., 1., 0.);
+    fraction_gen.set_probability(get_probability_table());
+    assert_eq!(get_chance(fraction_gen, 1.5), 0.4);
+    assert_eq!(get_chance(fraction_gen, 2.), 0.1);
+  }
+
+  #[test]
+  fn should_tilt_towards_long_or_short_notes() {
+    let fraction_gen = &mut TimeFractionGenerator::new();
+
+    fraction_gen.set_bias(0., 0., 1.);
+    fraction_gen.set_probability(get_probability_table());
+    assert!(get_chance(fraction_gen, 2.) > get_chance(fraction_gen, 1.));
+    assert!(get_chance(fraction_gen, 1.) > get_chance(fraction_gen, 4_f32.recip()));
+
+    fraction_gen.set_bias(0., 0., -1.);
+    fraction_gen.set_probability(get_probability_table());
+    assert!(get_chance(fraction_gen, 2.) < get_chance(fraction_gen, 1.));
+    assert!(get_chance(fraction_gen, 1.) < get_chance(fraction_gen, 4_f32.recip()));
+  }
+
+  #[test]
+  fn should_ignore_a_bias_that_leaves_out_every_note() {
+    let fraction_gen = &mut TimeFractionGenerator::new();
+    let mut probability = get_probability_table().map(|(_, value)| (0., value));
+    probability[3].0 = 1.;
+
+    fraction_gen.set_bias(-1., 0., 0.);
+    fraction_gen.set_probability(probability);
+    assert_eq!(get_chance(fraction_gen, 1.33333333), 1.);
+  }
+
+  #[test]
+  fn should_return_the_distribution() {
+    let fraction_gen = &mut TimeFractionGenerator::new();
+    fraction_gen.set_probability(get_probability_table());
+    let distribution = fraction_gen.get_distribution();
+    assert_eq!(
+      distribution
+        .iter()
+        .filter(|chance| (*chance - 1. / 7.).abs() < 0.0001)
+        .count(),
+      7
+    );
+    assert!((distribution.iter().sum::<f32>() - 1.).abs() < 0.0001);
+
+    fraction_gen.set_probability(get_probability_table().map(|(_, value)| (0., value)));
+    assert!(fraction_gen
+      .get_distribution()
+      .iter()
+      .all(|chance| (chance - 1. / 15.).abs() < 0.0001));
+  }
+
+  #[test]
+  fn should_use_the_forced_fraction_for_the_next_manual_trigger() {
+    let fraction_gen = &mut TimeFractionGenerator::new();
+    fraction_gen.set_probability(std::array::from_fn(|index| {
+      (
+        if index == 0 { 1. } else { 0. },
+        2_f32.powi(-(index as i32)),
+      )
+    }));
+    fraction_gen.force_fraction(0.25);
+
+    assert_eq!(fraction_gen.process(true, false), 1.);
+    assert_eq!(fraction_gen.process(false, false), 1.);
+    assert_eq!(fraction_gen.process(true, true), 0.25);
+    assert_eq!(fraction_gen.process(false, false), 0.25);
+    assert_eq!(fraction_gen.process(true, true), 1.);
+  }
 }
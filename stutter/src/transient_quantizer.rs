@@ -0,0 +1,189 @@
+use crate::{onset_detector::OnsetDetector, shared::float_ext::FloatExt};
+
+// Start the slice slightly before the detected onset, because the detector needs a moment to
+// respond to the attack.
+const PRE_ROLL: f32 = 2.;
+
+/// Holds back a trigger until the next transient in the input arrives, so the stutter starts on
+/// the attack. A slice that starts on the transient can only be captured once it has been
+/// recorded, so its first pass plays the input as it comes in and it's captured one delay time
+/// later, right when it starts to repeat. The repeats line up with the transient without any
+/// look-ahead, so this doesn't add latency.
+pub struct TransientQuantizer {
+  onset_detector: OnsetDetector,
+  waiting: Option<usize>,
+  capturing: Option<usize>,
+  is_manual_trigger: bool,
+  window: f32,
+  sample_rate: f32,
+}
+
+impl TransientQuantizer {
+  pub fn new(sample_rate: f32) -> Self {
+    Self {
+      onset_detector: OnsetDetector::new(sample_rate),
+      waiting: None,
+      capturing: None,
+      is_manual_trigger: false,
+      window: 0.,
+      sample_rate,
+    }
+  }
+
+  /// A window of zero milliseconds disables the quantization.
+  pub fn set_parameters(&mut self, window: f32, sensitivity: f32) {
+    self.window = window;
+    self.onset_detector.set_parameters(sensitivity, 0.);
+  }
+
+  /// Returns the trigger, whether it originated from a manual trigger and whether the slice has
+  /// to be captured. Unquantized triggers capture right away, while a trigger on a transient
+  /// captures one delay time later.
+  pub fn process(
+    &mut self,
+    input: f32,
+    trigger: bool,
+    manual_trigger: bool,
+    delay_time: f32,
+  ) -> (bool, bool, bool) {
+    let is_onset = self.onset_detector.process(input);
+
+    if self.window <= 0. {
+      self.waiting = None;
+      self.capturing = None;
+      return (trigger, manual_trigger, trigger);
+    }
+
+    if trigger {
+      self.waiting = Some(self.window.mstosamps(self.sample_rate) as usize);
+      self.is_manual_trigger = manual_trigger;
+    }
+
+    // A new trigger doesn't cancel the capture of the slice that is playing, until it starts a
+    // slice of its own.
+    let capture = match self.capturing {
+      Some(0) => {
+        self.capturing = None;
+        true
+      }
+      Some(remaining) => {
+        self.capturing = Some(remaining - 1);
+        false
+      }
+      None => false,
+    };
+
+    match self.waiting {
+      Some(_) if is_onset => {
+        self.waiting = None;
+        let capture_time = (delay_time - PRE_ROLL).max(0.).mstosamps(self.sample_rate) as usize;
+        self.capturing = capture_time.checked_sub(1);
+        (true, self.is_manual_trigger, capture_time == 0)
+      }
+      Some(0) => {
+        // No transient arrived in time, so capture right away like an unquantized trigger.
+        self.waiting = None;
+        self.capturing = None;
+        (true, self.is_manual_trigger, true)
+      }
+      Some(remaining) => {
+        self.waiting = Some(remaining - 1);
+        (false, false, capture)
+      }
+      None => (false, false, capture),
+    }
+  }
+
+  /// Returns whether the first pass of a slice plays the input, because it hasn't been captured
+  /// yet.
+  pub fn is_capturing(&self) -> bool {
+    self.capturing.is_some()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::TransientQuantizer;
+
+  // Returns the samples at which a trigger and a capture happen.
+  fn find_events(
+    quantizer: &mut TransientQuantizer,
+    signal: &[f32],
+    triggers: &[usize],
+    delay_time: f32,
+  ) -> (Vec<usize>, Vec<usize>) {
+    let mut trigger_times = Vec::new();
+    let mut capture_times = Vec::new();
+    for (i, x) in signal.iter().enumerate() {
+      let (trigger, _, capture) = quantizer.process(*x, triggers.contains(&i), true, delay_time);
+      if trigger {
+        trigger_times.push(i);
+      }
+      if capture {
+        capture_times.push(i);
+      }
+    }
+    (trigger_times, capture_times)
+  }
+
+  fn get_hit(onset: i32) -> Vec<f32> {
+    (0..200)
+      .map(|i| {
+        if i < onset {
+          0.
+        } else {
+          0.9_f32.powi(i - onset)
+        }
+      })
+      .collect()
+  }
+
+  #[test]
+  fn should_pass_triggers_through_when_disabled() {
+    let mut quantizer = TransientQuantizer::new(1000.);
+    quantizer.set_parameters(0., 0.5);
+
+    assert_eq!(quantizer.process(0., true, true, 100.), (true, true, true));
+    assert_eq!(
+      quantizer.process(0., false, false, 100.),
+      (false, false, false)
+    );
+  }
+
+  #[test]
+  fn should_trigger_on_the_transient_and_capture_one_delay_time_later() {
+    let mut quantizer = TransientQuantizer::new(1000.);
+    quantizer.set_parameters(100., 0.5);
+
+    // The slice is captured a couple of milliseconds early, so it starts just before the onset
+    // was detected
+    assert_eq!(
+      find_events(&mut quantizer, &get_hit(30), &[0], 50.),
+      (vec![30], vec![78])
+    );
+  }
+
+  #[test]
+  fn should_not_cancel_a_pending_capture() {
+    let mut quantizer = TransientQuantizer::new(1000.);
+    quantizer.set_parameters(100., 0.5);
+
+    // Another trigger waits for a transient while the first slice plays its first pass. None
+    // arrives, so it falls back to a regular trigger after the first slice was captured.
+    assert_eq!(
+      find_events(&mut quantizer, &get_hit(30), &[0, 60], 50.),
+      (vec![30, 160], vec![78, 160])
+    );
+  }
+
+  #[test]
+  fn should_fall_back_to_a_regular_trigger_without_transients() {
+    let mut quantizer = TransientQuantizer::new(1000.);
+    quantizer.set_parameters(20., 0.5);
+
+    assert_eq!(
+      find_events(&mut quantizer, &[0.; 100], &[0], 50.),
+      (vec![20], vec![20])
+    );
+  }
+}
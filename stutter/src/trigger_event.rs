@@ -0,0 +1,12 @@
+/// A record of a single trigger, for displaying or logging the pattern the stutter plays.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TriggerEvent {
+  /// The amount of processed samples at the moment of the trigger.
+  pub time: u64,
+  /// The note value of the slice, relative to the pulse.
+  pub time_fraction: f32,
+  /// How many slice lengths it takes until the next auto trigger.
+  pub duration_multiple: f32,
+  /// Whether the slice can be heard, or whether the chance or the on switch rejected it.
+  pub is_active: bool,
+}